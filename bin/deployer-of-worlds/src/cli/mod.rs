@@ -1,5 +1,7 @@
 use alloy::{
+    dyn_abi::DynSolType,
     network::{EthereumWallet, IntoWallet},
+    primitives::keccak256,
     providers::ProviderBuilder,
     signers::{
         k256::ecdsa::SigningKey,
@@ -8,8 +10,15 @@ use alloy::{
 };
 use anyhow::Result;
 use clap_derive::{Parser, Subcommand};
-use deployer::{config::config::Config, executor::Executor};
-use std::path::PathBuf;
+use deployer::{
+    config::config::{write_overrides, Config},
+    data_indexer::DataIndexer,
+    execution::{build_initcode, predict_deployment, proxy_deployment_data, CREATE2_DEPLOYER},
+    executor::Executor,
+    salt_miner::{mine_salt, SaltPattern},
+};
+use deployer_core::{ActionData, DeploymentData, Literal, SecretStore, Variable, VariableValue};
+use std::{collections::HashMap, path::PathBuf};
 
 #[derive(Debug, Parser)]
 #[command(author, about = "Deployer Of Worlds", long_about = None)]
@@ -22,6 +31,9 @@ pub struct Cli {
     pub rpc_url: String,
     #[arg(long, env = "PRIVATE_KEY", default_value = "")]
     pub private_key: String,
+    /// YAML or JSON file of `name: value` pairs for `!secret` values
+    #[arg(long, env = "SECRETS_FILE", default_value = ".secrets.yml")]
+    pub secrets_file: PathBuf,
 }
 
 impl Cli {
@@ -32,14 +44,134 @@ impl Cli {
         match self.command {
             Commands::Start(ref args) => self.start_server(args).await,
             Commands::Execute(ref args) => self.execute_pipeline(args).await,
+            Commands::AddressOf(ref args) => self.address_of(args),
+            Commands::MineSalt(ref args) => self.mine_salt(args),
+        }
+    }
+
+    /// Load a pipeline from the pipelines directory and apply its inputs
+    fn load_pipeline(&self, args: &PipelineArgs) -> Result<Config> {
+        let config_path = PathBuf::from(&self.data_dir)
+            .join("pipelines")
+            .join(&args.config);
+        let mut config = Config::load_from_file(config_path.to_str().unwrap())?;
+
+        // Reject missing or invalid inputs before anything touches the chain
+        config.apply_inputs(&args.collect_inputs()?)?;
+        for path in &args.overrides {
+            config.apply_overrides(path)?;
+        }
+        Ok(config)
+    }
+
+    /// Load a pipeline's deploy action along with an offline resolver for it
+    fn load_deploy_action(
+        &self,
+        args: &PipelineArgs,
+        action_id: &str,
+    ) -> Result<(DeploymentData, DataIndexer)> {
+        let config = self.load_pipeline(args)?;
+        let action = config
+            .actions
+            .iter()
+            .find(|action| action.id == action_id)
+            .ok_or_else(|| anyhow::anyhow!("Action {} not found", action_id))?;
+
+        let data_dir = PathBuf::from(&self.data_dir).join("data");
+        let secrets = SecretStore::load(&self.secrets_file)?;
+        let indexer = DataIndexer::for_config(&config, data_dir, secrets)?;
+
+        let data = match &action.action_data {
+            ActionData::Deploy(data) => data.clone(),
+            // The proxy's constructor args depend on the implementation's address
+            ActionData::DeployProxy(data) => {
+                let implementation = match (&data.implementation, &data.implementation_address) {
+                    (Some(implementation), _) => {
+                        Some(predict_deployment(implementation, &indexer)?.address)
+                    }
+                    (None, Some(address)) => address
+                        .resolve(DynSolType::Address, &indexer)?
+                        .as_address(),
+                    (None, None) => None,
+                };
+                proxy_deployment_data(data, implementation, &indexer)?
+            }
+            _ => anyhow::bail!("Action {} is not a deploy action", action_id),
+        };
+        Ok((data, indexer))
+    }
+
+    fn mine_salt(&self, args: &MineSaltArgs) -> Result<()> {
+        let (data, indexer) = self.load_deploy_action(&args.pipeline, &args.action)?;
+        let pattern = SaltPattern::new(
+            args.prefix.as_deref(),
+            args.suffix.as_deref(),
+            args.leading_zero_bytes,
+        )?;
+
+        // The salt isn't part of the initcode, so hash it once up front
+        let init_code_hash = keccak256(&build_initcode(&data, &indexer)?.code);
+        let threads = args.threads.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        });
+
+        tracing::info!("Mining salt on {} threads", threads);
+        let started = std::time::Instant::now();
+        let mined = mine_salt(
+            CREATE2_DEPLOYER,
+            init_code_hash,
+            &pattern,
+            threads,
+            args.max_attempts,
+        )
+        .ok_or_else(|| anyhow::anyhow!("No matching salt found"))?;
+
+        tracing::info!(
+            "Found salt after {} attempts in {:.1?}",
+            mined.attempts,
+            started.elapsed()
+        );
+        println!("salt: 0x{:x}", mined.salt);
+        println!("address: {}", mined.address.to_checksum(None));
+
+        if let Some(path) = &args.write {
+            let VariableValue::Var(name) = &data.salt else {
+                anyhow::bail!("The action's salt must be a !var to write an override");
+            };
+            let variable = Variable {
+                ty: "bytes32".to_string(),
+                value: Literal::Scalar(format!("0x{:x}", mined.salt)),
+            };
+            write_overrides(path, HashMap::from([(name.clone(), variable)]))?;
+            tracing::info!("Wrote {} to {}", name, path.display());
         }
+
+        Ok(())
+    }
+
+    fn address_of(&self, args: &AddressOfArgs) -> Result<()> {
+        let (data, indexer) = self.load_deploy_action(&args.pipeline, &args.action)?;
+        let deployment = predict_deployment(&data, &indexer)?;
+
+        tracing::info!(
+            "Factory 0x{:x}, salt 0x{:x}, initcode hash 0x{:x}",
+            deployment.factory,
+            deployment.salt,
+            keccak256(&deployment.initcode.code)
+        );
+        println!("{}", deployment.address.to_checksum(None));
+        Ok(())
     }
 
     async fn start_server(&self, args: &StartArgs) -> Result<()> {
         tracing::info!("Starting server on port {}", args.port);
 
+        let secrets = SecretStore::load(&self.secrets_file)?;
         let server =
-            crate::server::create_server(self.data_dir.clone(), self.rpc_url.clone()).await?;
+            crate::server::create_server(self.data_dir.clone(), self.rpc_url.clone(), secrets)
+                .await?;
         let listener = tokio::net::TcpListener::bind(&format!("0.0.0.0:{}", args.port)).await?;
 
         tracing::info!("Server listening on http://0.0.0.0:{}", args.port);
@@ -49,13 +181,9 @@ impl Cli {
     }
 
     async fn execute_pipeline(&self, args: &ExecuteArgs) -> Result<()> {
-        tracing::info!("Executing pipeline: {}", args.config);
+        tracing::info!("Executing pipeline: {}", args.pipeline.config);
 
-        // Load configuration from pipelines directory
-        let config_path = PathBuf::from(&self.data_dir)
-            .join("pipelines")
-            .join(&args.config);
-        let config = Config::load_from_file(config_path.to_str().unwrap())?;
+        let config = self.load_pipeline(&args.pipeline)?;
 
         if args.dry_run {
             tracing::info!(
@@ -72,16 +200,38 @@ impl Cli {
         } else {
             let signer: PrivateKeySigner =
                 self.private_key.parse().expect("should parse private key");
-            let wallet = EthereumWallet::new(signer);
+            let wallet = EthereumWallet::new(signer.clone());
             let provider = ProviderBuilder::new()
                 .wallet(wallet)
                 .connect_http(self.rpc_url.parse()?);
 
             // Create executor with data directory
             let data_dir = PathBuf::from(&self.data_dir).join("data");
-            let mut executor = Executor::with_data_dir(provider, data_dir);
+            let secrets = SecretStore::load(&self.secrets_file)?;
+            let mut executor = Executor::with_data_dir(provider, data_dir)
+                .with_secrets(secrets)
+                .with_signer(signer);
             executor.register_config(config)?;
             executor.execute_actions().await?;
+
+            for verification in &executor.run_output().verifications {
+                tracing::info!(
+                    "Verification of {} at 0x{:x}: {:?} ({})",
+                    verification.action_id,
+                    verification.address,
+                    verification.status,
+                    verification.message
+                );
+            }
+            for safe_transaction in &executor.run_output().safe_transactions {
+                tracing::info!(
+                    "Safe transaction 0x{:x} with {} at nonce {}: {}",
+                    safe_transaction.safe_tx_hash,
+                    safe_transaction.actions.join(", "),
+                    safe_transaction.nonce,
+                    safe_transaction.destination
+                );
+            }
         }
 
         tracing::info!("Pipeline execution completed successfully");
@@ -97,12 +247,72 @@ pub struct StartArgs {
 
 #[derive(Debug, Parser)]
 pub struct ExecuteArgs {
-    #[arg(help = "Configuration file name (e.g., deploy.yml)")]
-    pub config: String,
+    #[command(flatten)]
+    pub pipeline: PipelineArgs,
     #[arg(short, long, help = "Dry run - don't execute transactions")]
     pub dry_run: bool,
 }
 
+#[derive(Debug, Parser)]
+pub struct MineSaltArgs {
+    #[command(flatten)]
+    pub pipeline: PipelineArgs,
+    #[arg(help = "Id of the deploy action")]
+    pub action: String,
+    #[arg(long, help = "Hex digits the address must start with")]
+    pub prefix: Option<String>,
+    #[arg(long, help = "Hex digits the address must end with")]
+    pub suffix: Option<String>,
+    #[arg(long, default_value = "0", help = "Minimum number of leading zero bytes")]
+    pub leading_zero_bytes: usize,
+    #[arg(long, help = "Worker threads, defaults to the number of CPUs")]
+    pub threads: Option<usize>,
+    #[arg(long, help = "Give up after this many salts")]
+    pub max_attempts: Option<u64>,
+    #[arg(long, help = "Write the salt to this variable override file")]
+    pub write: Option<PathBuf>,
+}
+
+#[derive(Debug, Parser)]
+pub struct AddressOfArgs {
+    #[command(flatten)]
+    pub pipeline: PipelineArgs,
+    #[arg(help = "Id of the deploy action")]
+    pub action: String,
+}
+
+/// A pipeline config and the values for its inputs
+#[derive(Debug, Parser)]
+pub struct PipelineArgs {
+    #[arg(help = "Configuration file name (e.g., deploy.yml)")]
+    pub config: String,
+    #[arg(long = "input", value_name = "KEY=VALUE", help = "Value for a pipeline input")]
+    pub inputs: Vec<String>,
+    #[arg(long, help = "JSON file of pipeline input values")]
+    pub inputs_file: Option<PathBuf>,
+    #[arg(long = "overrides", help = "Variable override file, e.g. written by mine-salt")]
+    pub overrides: Vec<PathBuf>,
+}
+
+impl PipelineArgs {
+    /// Input values from the inputs file, overridden by `--input` flags
+    fn collect_inputs(&self) -> Result<HashMap<String, Literal>> {
+        let mut inputs: HashMap<String, Literal> = match &self.inputs_file {
+            Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            None => HashMap::new(),
+        };
+
+        for input in &self.inputs {
+            let (key, value) = input
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid input {}, expected KEY=VALUE", input))?;
+            inputs.insert(key.to_string(), Literal::Scalar(value.to_string()));
+        }
+
+        Ok(inputs)
+    }
+}
+
 /// Commands to be executed
 #[derive(Debug, Subcommand)]
 pub enum Commands {
@@ -112,4 +322,10 @@ pub enum Commands {
     /// Execute a pipeline from config file
     #[command(name = "execute")]
     Execute(ExecuteArgs),
+    /// Print the CREATE2 address of a deploy action without touching the chain
+    #[command(name = "address-of")]
+    AddressOf(AddressOfArgs),
+    /// Search for a salt giving a deploy action a vanity CREATE2 address
+    #[command(name = "mine-salt")]
+    MineSalt(MineSaltArgs),
 }
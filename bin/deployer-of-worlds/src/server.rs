@@ -8,7 +8,7 @@ use axum::{
     Router,
 };
 use deployer::{config::config::Config, executor::Executor};
-use deployer_core::{Action, Variable};
+use deployer_core::{Action, RunOutput, SecretStore, Variable};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::RwLock;
@@ -18,6 +18,7 @@ use tracing::{error, info};
 pub struct AppState {
     pub data_dir: String,
     pub rpc_url: String,
+    pub secrets: SecretStore,
     pub configs: Arc<RwLock<HashMap<String, Config>>>,
 }
 
@@ -39,6 +40,7 @@ pub struct ExecutionResponse {
     pub success: bool,
     pub message: String,
     pub execution_id: Option<String>,
+    pub output: Option<RunOutput>,
 }
 
 #[derive(Serialize)]
@@ -52,10 +54,15 @@ pub struct HealthResponse {
     pub version: String,
 }
 
-pub async fn create_server(data_dir: String, rpc_url: String) -> Result<Router> {
+pub async fn create_server(
+    data_dir: String,
+    rpc_url: String,
+    secrets: SecretStore,
+) -> Result<Router> {
     let state = AppState {
         data_dir: data_dir.clone(),
         rpc_url,
+        secrets,
         configs: Arc::new(RwLock::new(HashMap::new())),
     };
 
@@ -114,6 +121,7 @@ async fn execute_pipeline(
                     success: false,
                     message: format!("Configuration '{}' not found", name),
                     execution_id: None,
+                    output: None,
                 }));
             }
         }
@@ -123,14 +131,16 @@ async fn execute_pipeline(
         config.clone(),
         &state.rpc_url,
         &state.data_dir,
+        &state.secrets,
         request.dry_run.unwrap_or(false),
     )
     .await
     {
-        Ok(execution_id) => Ok(Json(ExecutionResponse {
+        Ok((execution_id, output)) => Ok(Json(ExecutionResponse {
             success: true,
             message: "Pipeline executed successfully".to_string(),
             execution_id: Some(execution_id),
+            output: Some(output),
         })),
         Err(e) => {
             error!("Pipeline execution failed: {}", e);
@@ -138,6 +148,7 @@ async fn execute_pipeline(
                 success: false,
                 message: format!("Execution failed: {}", e),
                 execution_id: None,
+                output: None,
             }))
         }
     }
@@ -155,22 +166,28 @@ async fn execute_one_off(
     // Create temporary config from request
     let config = Config {
         variables: request.variables,
+        inputs: HashMap::new(),
         data: HashMap::new(), // No data references for one-off pipelines
         actions: request.actions,
+        verification: None,
+        safe: None,
+        read_batch: None,
     };
 
     match execute_config(
         config.clone(),
         &state.rpc_url,
         &state.data_dir,
+        &state.secrets,
         request.dry_run.unwrap_or(false),
     )
     .await
     {
-        Ok(execution_id) => Ok(Json(ExecutionResponse {
+        Ok((execution_id, output)) => Ok(Json(ExecutionResponse {
             success: true,
             message: "One-off pipeline executed successfully".to_string(),
             execution_id: Some(execution_id),
+            output: Some(output),
         })),
         Err(e) => {
             error!("One-off pipeline execution failed: {}", e);
@@ -178,12 +195,19 @@ async fn execute_one_off(
                 success: false,
                 message: format!("Execution failed: {}", e),
                 execution_id: None,
+                output: None,
             }))
         }
     }
 }
 
-async fn execute_config(config: Config, rpc_url: &str, data_dir: &str, dry_run: bool) -> Result<String> {
+async fn execute_config(
+    config: Config,
+    rpc_url: &str,
+    data_dir: &str,
+    secrets: &SecretStore,
+    dry_run: bool,
+) -> Result<(String, RunOutput)> {
     let execution_id = uuid::Uuid::new_v4().to_string();
 
     if dry_run {
@@ -197,18 +221,19 @@ async fn execute_config(config: Config, rpc_url: &str, data_dir: &str, dry_run:
                 action.id, action.action_data
             );
         }
-        return Ok(execution_id);
+        return Ok((execution_id, RunOutput::default()));
     }
 
     // Create provider
     let provider = ProviderBuilder::new().connect_http(rpc_url.parse()?);
 
     // Create executor with data directory
-    let mut executor = Executor::new(provider);
+    let data_dir = std::path::Path::new(data_dir).join("data");
+    let mut executor = Executor::with_data_dir(provider, data_dir).with_secrets(secrets.clone());
     executor.register_config(config)?;
     executor.execute_actions().await?;
 
-    Ok(execution_id)
+    Ok((execution_id, executor.run_output().clone()))
 }
 
 async fn load_configs(state: &AppState) -> Result<()> {
@@ -1,9 +1,11 @@
-use crate::{variables::VariableValue, Result, VariableResolver};
+use crate::{variables::VariableValue, DeployerError, Result, VariableResolver};
 use alloy::{
-    dyn_abi::{DynSolValue, FunctionExt, JsonAbiExt},
+    dyn_abi::{DynSolType, DynSolValue, FunctionExt, JsonAbiExt},
+    eips::BlockId,
     providers::network::TransactionBuilder,
 };
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, str::FromStr};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "content", rename_all = "snake_case")]
@@ -11,37 +13,480 @@ pub enum ActionData {
     Deploy(DeploymentData),
     Write(WriteData),
     Read(ReadData),
+    DeployProxy(ProxyDeploymentData),
+    UpgradeProxy(ProxyUpgradeData),
+    Batch(BatchData),
+    ReadStorage(StorageReadData),
+    ReadChain(ChainReadData),
+    Transfer(TransferData),
+    RawCall(RawCallData),
+    RawTransaction(RawTransactionData),
+    Assert(AssertData),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeploymentData {
-    pub address: VariableValue,
+    /// Expected CREATE2 address. When omitted it is computed from the
+    /// factory, salt and initcode; when given, the computed address must match
+    #[serde(default)]
+    pub address: Option<VariableValue>,
     pub constructor_args: Vec<VariableValue>,
     pub salt: VariableValue,
-    pub constructor_abi_item: String,
-    pub bytecode: VariableValue,
+    /// Constructor signature; defaults to the constructor in the `contract` ABI
+    #[serde(default)]
+    pub constructor_abi_item: Option<String>,
+    /// Creation bytecode; defaults to the bytecode of `contract`
+    #[serde(default)]
+    pub bytecode: Option<VariableValue>,
+    /// Data reference of the contract providing bytecode, constructor and errors.
+    /// For reverts, defaults to the data reference used for `bytecode`, if any
+    #[serde(default)]
+    pub contract: Option<String>,
+    /// Additional error signatures used to decode reverts,
+    /// e.g. `error Unauthorized(address caller)`
+    #[serde(default)]
+    pub errors: Vec<String>,
+    /// Addresses of external libraries to link into the bytecode, keyed by
+    /// library name or fully qualified name (`src/MyLib.sol:MyLib`)
+    #[serde(default)]
+    pub libraries: HashMap<String, VariableValue>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WriteData {
     pub address: VariableValue,
-    pub abi_item: String,
+    /// Human-readable function signature; optional when `contract` and `function` are set
+    #[serde(default)]
+    pub abi_item: Option<String>,
     pub args: Vec<VariableValue>,
     pub value: VariableValue,
+    /// Data reference of the contract whose ABI is used to look up `function`
+    /// and decode reverts
+    #[serde(default)]
+    pub contract: Option<String>,
+    /// Function name, or full signature such as `addStake(uint32,uint256)` for overloads
+    #[serde(default)]
+    pub function: Option<String>,
+    /// Additional error signatures used to decode reverts,
+    /// e.g. `error Unauthorized(address caller)`
+    #[serde(default)]
+    pub errors: Vec<String>,
+    /// Send from the pipeline's key or batch into its Safe transaction;
+    /// defaults to the pipeline's `safe.sender`
+    #[serde(default)]
+    pub sender: Option<crate::Sender>,
     // /// Optional condition that must be met for this write to execute
     // #[serde(default)]
     // pub condition: Option<Condition>,
 }
 
+/// Native value sent from the pipeline's key. The amount sent is indexed as
+/// `<id>.amount`, zero when a top-up wasn't needed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferData {
+    pub to: VariableValue,
+    /// Amount in wei, or with a unit such as `0.5 ether`; the balance to reach
+    /// when `top_up` is set
+    pub amount: VariableValue,
+    /// Calldata sent along with the value
+    #[serde(default)]
+    pub data: Option<VariableValue>,
+    /// Only send the difference between `to`'s balance and `amount`
+    #[serde(default)]
+    pub top_up: bool,
+}
+
+/// Calldata produced elsewhere, e.g. a governance proposal, sent as-is from
+/// the pipeline's key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawCallData {
+    pub to: VariableValue,
+    /// Hex calldata
+    pub data: VariableValue,
+    #[serde(default = "default_value")]
+    pub value: VariableValue,
+    /// Error signatures used to decode reverts
+    #[serde(default)]
+    pub errors: Vec<String>,
+    /// Skip the call unless this holds
+    #[serde(default)]
+    pub condition: Option<Condition>,
+}
+
+/// A signed transaction broadcast as-is, e.g. a keyless deployment. If it was
+/// already mined, its existing receipt is used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawTransactionData {
+    /// EIP-2718 encoded signed transaction
+    pub transaction: VariableValue,
+    /// Skip the broadcast unless this holds
+    #[serde(default)]
+    pub condition: Option<Condition>,
+}
+
+fn default_value() -> VariableValue {
+    VariableValue::Value("0".to_string())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReadData {
     pub address: VariableValue,
     pub args: Vec<VariableValue>,
-    pub abi_item: String,
+    /// Human-readable function signature; optional when `contract` and `function` are set
+    #[serde(default)]
+    pub abi_item: Option<String>,
+    /// Data reference of the contract whose ABI is used to look up `function`
+    #[serde(default)]
+    pub contract: Option<String>,
+    /// Function name, or full signature such as `balanceOf(address)` for overloads
+    #[serde(default)]
+    pub function: Option<String>,
+    /// Block to read at instead of `latest`, see [`resolve_block`]. Reads with
+    /// their own block are never batched
+    #[serde(default)]
+    pub block: Option<VariableValue>,
+}
+
+/// Resolve a `block` field: a number, block hash or tag such as `finalized`
+/// or `safe`, or a reference to one, e.g. `!output deploy_token.block_number`
+pub fn resolve_block<R: VariableResolver>(block: &VariableValue, resolver: &R) -> Result<BlockId> {
+    let parse = |value: &str| {
+        BlockId::from_str(value)
+            .map_err(|e| DeployerError::Config(format!("Invalid block {}: {}", value, e)))
+    };
+    if let VariableValue::Value(value) = block {
+        return parse(value);
+    }
+
+    match block.resolve(DynSolType::Uint(64), resolver)? {
+        DynSolValue::Uint(number, _) => number
+            .try_into()
+            .map(BlockId::number)
+            .map_err(|_e| DeployerError::Config(format!("Invalid block number {}", number))),
+        DynSolValue::FixedBytes(hash, 32) => Ok(BlockId::hash(hash)),
+        DynSolValue::String(value) => parse(&value),
+        other => Err(DeployerError::TypeConversion {
+            expected: "block number, hash or tag".to_string(),
+            actual: format!("{:?}", other),
+        }),
+    }
+}
+
+/// Read of a raw storage slot with `eth_getStorageAt`, for state that has no
+/// view function. The value is indexed under the action id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageReadData {
+    pub address: VariableValue,
+    /// Slot as a number or 32-byte word; the mapping's slot when `keys` are set
+    #[serde(default)]
+    pub slot: Option<VariableValue>,
+    /// ERC-1967 slot to read instead of `slot`
+    #[serde(default)]
+    pub named_slot: Option<NamedSlot>,
+    /// Mapping keys, outermost first; each gives `keccak256(key . slot)`
+    #[serde(default)]
+    pub keys: Vec<StorageKey>,
+    /// Solidity value type the slot is decoded as
+    #[serde(default = "default_storage_type")]
+    pub ty: String,
+    /// Byte offset of a packed value from the low-order end of the slot, as
+    /// reported in solc's storage layout
+    #[serde(default)]
+    pub offset: usize,
+    /// Block to read at instead of `latest`, see [`resolve_block`]
+    #[serde(default)]
+    pub block: Option<VariableValue>,
+}
+
+fn default_storage_type() -> String {
+    "bytes32".to_string()
+}
+
+/// ERC-1967 proxy slots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NamedSlot {
+    Implementation,
+    Admin,
+    Beacon,
+}
+
+/// Mapping key used to derive a storage slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageKey {
+    /// Solidity type of the mapping's key, e.g. `address` or `string`
+    pub ty: String,
+    pub value: VariableValue,
+}
+
+/// Read of account or chain state, indexed under the action id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainReadData {
+    pub query: ChainQuery,
+    /// Account for `balance`, `nonce`, `code_hash` and `code_size`
+    #[serde(default)]
+    pub address: Option<VariableValue>,
+    /// Block to read at instead of `latest`, see [`resolve_block`]
+    #[serde(default)]
+    pub block: Option<VariableValue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainQuery {
+    /// Native balance in wei
+    Balance,
+    /// Transaction count
+    Nonce,
+    /// keccak256 of the runtime code
+    CodeHash,
+    /// Length of the runtime code in bytes
+    CodeSize,
+    /// Number of the block read at
+    BlockNumber,
+    ChainId,
+    /// Timestamp of the block read at
+    Timestamp,
+}
+
+impl ChainQuery {
+    /// Solidity type of the value the query returns
+    pub fn sol_type(&self) -> &'static str {
+        match self {
+            ChainQuery::CodeHash => "bytes32",
+            _ => "uint256",
+        }
+    }
+
+    /// Name of the query as written in configs
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChainQuery::Balance => "balance",
+            ChainQuery::Nonce => "nonce",
+            ChainQuery::CodeHash => "code_hash",
+            ChainQuery::CodeSize => "code_size",
+            ChainQuery::BlockNumber => "block_number",
+            ChainQuery::ChainId => "chain_id",
+            ChainQuery::Timestamp => "timestamp",
+        }
+    }
+}
+
+impl ChainReadData {
+    pub async fn read<P, R>(&self, provider: &P, resolver: &R) -> Result<DynSolValue>
+    where
+        P: alloy::providers::Provider,
+        R: VariableResolver,
+    {
+        let block = match &self.block {
+            Some(block) => resolve_block(block, resolver)?,
+            None => BlockId::latest(),
+        };
+        let failed = |e: alloy::transports::TransportError| {
+            DeployerError::Execution(format!("Failed to read {:?}: {}", self.query, e))
+        };
+        let address = || -> Result<alloy::primitives::Address> {
+            self.address
+                .as_ref()
+                .ok_or_else(|| {
+                    DeployerError::Config(format!("{:?} read requires an address", self.query))
+                })?
+                .resolve(DynSolType::Address, resolver)?
+                .as_address()
+                .ok_or_else(|| DeployerError::Config("Invalid address".to_string()))
+        };
+        let uint = |value: u64| DynSolValue::Uint(alloy::primitives::U256::from(value), 256);
+
+        let value = match self.query {
+            ChainQuery::Balance => DynSolValue::Uint(
+                provider.get_balance(address()?).block_id(block).await.map_err(failed)?,
+                256,
+            ),
+            ChainQuery::Nonce => uint(
+                provider
+                    .get_transaction_count(address()?)
+                    .block_id(block)
+                    .await
+                    .map_err(failed)?,
+            ),
+            ChainQuery::CodeHash => {
+                let code = provider.get_code_at(address()?).block_id(block).await.map_err(failed)?;
+                DynSolValue::FixedBytes(alloy::primitives::keccak256(&code), 32)
+            }
+            ChainQuery::CodeSize => {
+                let code = provider.get_code_at(address()?).block_id(block).await.map_err(failed)?;
+                uint(code.len() as u64)
+            }
+            ChainQuery::BlockNumber | ChainQuery::Timestamp => {
+                let header = provider
+                    .get_block(block)
+                    .await
+                    .map_err(failed)?
+                    .ok_or_else(|| DeployerError::Execution(format!("Block {} not found", block)))?
+                    .header;
+                if self.query == ChainQuery::BlockNumber {
+                    uint(header.number)
+                } else {
+                    uint(header.timestamp)
+                }
+            }
+            ChainQuery::ChainId => uint(provider.get_chain_id().await.map_err(failed)?),
+        };
+
+        Ok(value)
+    }
+}
+
+/// Batching of read actions through Multicall3 `aggregate3`. Reads that
+/// become ready at the same point of the dependency graph share calls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadBatchConfig {
+    /// Maximum number of reads per `aggregate3` call
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Block the reads are made at: a number, `latest`, `safe` or `finalized`
+    #[serde(default)]
+    pub block: Option<String>,
+    /// Multicall3 address; defaults to the canonical deployment
+    #[serde(default)]
+    pub multicall: Option<VariableValue>,
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+/// Writes sent together in one Multicall3 `aggregate3Value` transaction.
+///
+/// Targets see Multicall3 as `msg.sender`, so calls needing the pipeline's
+/// own account or Safe should stay separate write actions. The transaction is
+/// indexed under the batch's id as `<id>.transaction_hash` and `<id>.block_number`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchData {
+    /// Executed in order
+    pub calls: Vec<BatchCall>,
+    /// Multicall3 address; defaults to the canonical deployment
+    #[serde(default)]
+    pub multicall: Option<VariableValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchCall {
+    /// Key the call's decoded return values are indexed under, as for a read action
+    pub id: String,
+    /// The call, which can't set `sender` as the whole batch is sent together
+    pub write: WriteData,
+    /// Let the rest of the batch go through if this call reverts
+    #[serde(default)]
+    pub allow_failure: bool,
+}
+
+/// Proxy standard, which decides the proxy constructor and how upgrades are sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyKind {
+    /// `ERC1967Proxy(implementation, data)`, upgraded with `upgradeToAndCall` on the proxy
+    #[default]
+    Erc1967,
+    /// Same proxy as `erc1967`; the implementation provides `upgradeToAndCall`
+    Uups,
+    /// `TransparentUpgradeableProxy(logic, initialOwner, data)`, upgraded through
+    /// the `ProxyAdmin` stored in the admin slot
+    Transparent,
+    /// `BeaconProxy(beacon, data)`, upgraded with `upgradeTo` on the beacon
+    Beacon,
+}
+
+/// The proxy contract itself; its constructor args are derived from the proxy kind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyContract {
+    /// Expected CREATE2 address of the proxy
+    #[serde(default)]
+    pub address: Option<VariableValue>,
+    pub salt: VariableValue,
+    /// Creation bytecode; defaults to the bytecode of `contract`
+    #[serde(default)]
+    pub bytecode: Option<VariableValue>,
+    /// Data reference of the proxy contract, e.g. `ERC1967Proxy`
+    #[serde(default)]
+    pub contract: Option<String>,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyDeploymentData {
+    #[serde(default)]
+    pub kind: ProxyKind,
+    /// Implementation to deploy, reused if it already exists at its CREATE2 address
+    #[serde(default)]
+    pub implementation: Option<Box<DeploymentData>>,
+    /// Address of an existing implementation, instead of deploying one
+    #[serde(default)]
+    pub implementation_address: Option<VariableValue>,
+    pub proxy: ProxyContract,
+    /// Initial owner of a transparent proxy's `ProxyAdmin`
+    #[serde(default)]
+    pub admin: Option<VariableValue>,
+    /// Beacon a beacon proxy points at
+    #[serde(default)]
+    pub beacon: Option<VariableValue>,
+    /// Initializer called through the proxy constructor, either a signature such as
+    /// `function initialize(address owner)` or a function name in the implementation's ABI
+    #[serde(default)]
+    pub initializer: Option<String>,
+    #[serde(default)]
+    pub initializer_args: Vec<VariableValue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyUpgradeData {
+    #[serde(default)]
+    pub kind: ProxyKind,
+    /// Address of the proxy being upgraded
+    pub proxy: VariableValue,
+    /// New implementation to deploy, reused if it already exists at its CREATE2 address
+    #[serde(default)]
+    pub implementation: Option<Box<DeploymentData>>,
+    /// Address of an existing new implementation, instead of deploying one
+    #[serde(default)]
+    pub implementation_address: Option<VariableValue>,
+    /// Data reference of the new implementation when `implementation_address`
+    /// is used; provides its ABI and storage layout
+    #[serde(default)]
+    pub contract: Option<String>,
+    /// Data reference of the implementation currently behind the proxy. Required
+    /// unless `unsafe_skip_storage_check` is set; the upgrade is blocked unless
+    /// the new storage layout is compatible with it
+    #[serde(default)]
+    pub previous_contract: Option<String>,
+    /// Upgrade even if the storage layouts are incompatible
+    #[serde(default)]
+    pub unsafe_skip_storage_check: bool,
+    /// Call made on the new implementation as part of the upgrade, in the same
+    /// form as `ProxyDeploymentData::initializer`; not supported for beacons
+    #[serde(default)]
+    pub call: Option<String>,
+    #[serde(default)]
+    pub call_args: Vec<VariableValue>,
+    /// Additional error signatures used to decode reverts of the upgrade
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+/// Post-check that fails the run when its condition doesn't hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssertData {
+    pub condition: Condition,
+    /// Shown when the assertion fails
+    #[serde(default)]
+    pub message: Option<String>,
 }
 
+/// Check of on-chain state or a previous output. Adjacently tagged like
+/// `ActionData` so values can use `!var` and the other YAML tags
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
+#[serde(tag = "type", content = "content", rename_all = "snake_case")]
 pub enum Condition {
     /// Compare a value from a contract call
     ContractCall {
@@ -49,6 +494,14 @@ pub enum Condition {
         abi_item: String,
         args: Vec<VariableValue>,
         comparison: Comparison,
+        /// Block to call at instead of `latest`, see [`resolve_block`]
+        #[serde(default)]
+        block: Option<VariableValue>,
+    },
+    /// Compare a balance, nonce or other account or chain state
+    ChainState {
+        read: ChainReadData,
+        comparison: Comparison,
     },
     /// Compare an output from a previous action
     OutputComparison {
@@ -72,11 +525,40 @@ pub enum Comparison {
     Eq(VariableValue),
     /// Not equal to the specified value
     Ne(VariableValue),
+    /// Array with an element equal to the value, or string or bytes
+    /// containing it
+    Contains(VariableValue),
+    /// Within `min` and `max`, inclusive
+    Between(Bounds),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bounds {
+    pub min: VariableValue,
+    pub max: VariableValue,
 }
 
 impl Condition {
     /// Evaluate the condition using the given provider and resolver
     pub async fn evaluate<P, R>(&self, provider: &P, resolver: &R) -> Result<bool>
+    where
+        P: alloy::providers::Provider,
+        R: VariableResolver,
+    {
+        let current_value = self.current_value(provider, resolver).await?;
+        self.comparison().evaluate(&current_value, resolver)
+    }
+
+    pub fn comparison(&self) -> &Comparison {
+        match self {
+            Condition::ContractCall { comparison, .. }
+            | Condition::ChainState { comparison, .. }
+            | Condition::OutputComparison { comparison, .. } => comparison,
+        }
+    }
+
+    /// The value the condition's comparison is applied to
+    pub async fn current_value<P, R>(&self, provider: &P, resolver: &R) -> Result<DynSolValue>
     where
         P: alloy::providers::Provider,
         R: VariableResolver,
@@ -86,7 +568,8 @@ impl Condition {
                 address,
                 abi_item,
                 args,
-                comparison,
+                block,
+                ..
             } => {
                 // Make a contract call to get the current value
                 let contract_address = address
@@ -98,28 +581,37 @@ impl Condition {
                     .parse()
                     .map_err(|e| crate::DeployerError::Config(format!("Invalid ABI: {}", e)))?;
 
+                if function.inputs.len() != args.len() {
+                    return Err(crate::DeployerError::Config(format!(
+                        "Function {} expects {} args but {} were provided",
+                        function.signature(),
+                        function.inputs.len(),
+                        args.len()
+                    )));
+                }
+
                 let call_args: Vec<DynSolValue> = args
                     .iter()
-                    .enumerate()
-                    .map(|(i, arg)| {
-                        let sol_type = alloy::dyn_abi::DynSolType::parse(&function.inputs[i].ty)
-                            .map_err(|e| {
-                                crate::DeployerError::Config(format!("Invalid type: {}", e))
-                            })?;
-                        arg.resolve(sol_type, resolver)
-                    })
+                    .zip(&function.inputs)
+                    .map(|(arg, input)| arg.resolve_param(input, resolver))
                     .collect::<Result<Vec<_>>>()?;
 
                 let call_data = function.abi_encode_input(&call_args).map_err(|e| {
                     crate::DeployerError::Config(format!("Failed to encode call: {}", e))
                 })?;
 
+                let block = match block {
+                    Some(block) => resolve_block(block, resolver)?,
+                    None => BlockId::latest(),
+                };
+
                 let result = provider
                     .call(
                         alloy::rpc::types::TransactionRequest::default()
                             .with_to(contract_address)
                             .with_input(call_data),
                     )
+                    .block(block)
                     .await
                     .map_err(|e| {
                         crate::DeployerError::Config(format!("Contract call failed: {}", e))
@@ -130,19 +622,13 @@ impl Condition {
                 })?;
 
                 // For simplicity, assume single return value for now
-                let current_value = decoded
-                    .first()
-                    .ok_or_else(|| crate::DeployerError::Config("No return value".to_string()))?;
-
-                comparison.evaluate(current_value, resolver)
-            }
-            Condition::OutputComparison {
-                output_ref,
-                comparison,
-            } => {
-                let current_value = resolver.get_output(output_ref)?;
-                comparison.evaluate(&current_value, resolver)
+                decoded
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| crate::DeployerError::Config("No return value".to_string()))
             }
+            Condition::ChainState { read, .. } => read.read(provider, resolver).await,
+            Condition::OutputComparison { output_ref, .. } => resolver.get_output(output_ref),
         }
     }
 }
@@ -173,15 +659,86 @@ impl Comparison {
             }
             Comparison::Eq(target) => {
                 let target_val = self.resolve_target_value(target, current_value, resolver)?;
-                Ok(self.compare_values(current_value, &target_val)? == 0)
+                self.values_equal(current_value, &target_val)
             }
             Comparison::Ne(target) => {
                 let target_val = self.resolve_target_value(target, current_value, resolver)?;
-                Ok(self.compare_values(current_value, &target_val)? != 0)
+                Ok(!self.values_equal(current_value, &target_val)?)
+            }
+            Comparison::Contains(target) => {
+                let Some(target_type) = contained_type(current_value) else {
+                    // Empty arrays carry no element type and contain nothing
+                    if let DynSolValue::Array(items) = current_value {
+                        if items.is_empty() {
+                            return Ok(false);
+                        }
+                    }
+                    return Err(crate::DeployerError::Config(format!(
+                        "Cannot check whether {} contains a value",
+                        format_sol_value(current_value)
+                    )));
+                };
+                let target_val = target.resolve(target_type, resolver)?;
+                match (current_value, &target_val) {
+                    (DynSolValue::Array(items) | DynSolValue::FixedArray(items), _) => {
+                        for item in items {
+                            if self.values_equal(item, &target_val)? {
+                                return Ok(true);
+                            }
+                        }
+                        Ok(false)
+                    }
+                    (DynSolValue::String(s), DynSolValue::String(needle)) => Ok(s.contains(needle.as_str())),
+                    _ => {
+                        let haystack = bytes_of(current_value).unwrap_or_default();
+                        let needle = bytes_of(&target_val).unwrap_or_default();
+                        Ok(needle.is_empty() || haystack.windows(needle.len()).any(|w| w == needle))
+                    }
+                }
+            }
+            Comparison::Between(Bounds { min, max }) => {
+                let min_val = self.resolve_target_value(min, current_value, resolver)?;
+                let max_val = self.resolve_target_value(max, current_value, resolver)?;
+                Ok(self.compare_values(current_value, &min_val)? >= 0
+                    && self.compare_values(current_value, &max_val)? <= 0)
             }
         }
     }
 
+    /// The expected side of the comparison, e.g. `>= 100`, with references
+    /// resolved against the type of `current_value`
+    pub fn describe<R: VariableResolver>(
+        &self,
+        current_value: &DynSolValue,
+        resolver: &R,
+    ) -> Result<String> {
+        let target = |value: &VariableValue| -> Result<String> {
+            if value.references_secret(resolver) {
+                return Ok(crate::REDACTED.to_string());
+            }
+            Ok(format_sol_value(&self.resolve_target_value(value, current_value, resolver)?))
+        };
+
+        Ok(match self {
+            Comparison::Lt(value) => format!("< {}", target(value)?),
+            Comparison::Lte(value) => format!("<= {}", target(value)?),
+            Comparison::Gt(value) => format!("> {}", target(value)?),
+            Comparison::Gte(value) => format!(">= {}", target(value)?),
+            Comparison::Eq(value) => format!("== {}", target(value)?),
+            Comparison::Ne(value) => format!("!= {}", target(value)?),
+            Comparison::Contains(value) => match contained_type(current_value) {
+                _ if value.references_secret(resolver) => format!("contains {}", crate::REDACTED),
+                Some(target_type) => {
+                    format!("contains {}", format_sol_value(&value.resolve(target_type, resolver)?))
+                }
+                None => format!("contains {:?}", value),
+            },
+            Comparison::Between(Bounds { min, max }) => {
+                format!("between {} and {}", target(min)?, target(max)?)
+            }
+        })
+    }
+
     fn resolve_target_value<R: VariableResolver>(
         &self,
         target: &VariableValue,
@@ -206,11 +763,82 @@ impl Comparison {
                 Ok(a_val.cmp(b_val) as i32)
             }
             (DynSolValue::Bool(a_val), DynSolValue::Bool(b_val)) => Ok(a_val.cmp(b_val) as i32),
+            (DynSolValue::String(a_val), DynSolValue::String(b_val)) => {
+                Ok(a_val.cmp(b_val) as i32)
+            }
+            (
+                DynSolValue::Bytes(_) | DynSolValue::FixedBytes(..),
+                DynSolValue::Bytes(_) | DynSolValue::FixedBytes(..),
+            ) => Ok(bytes_of(a).cmp(&bytes_of(b)) as i32),
             _ => Err(crate::DeployerError::Config(
                 "Cannot compare values of different types".to_string(),
             )),
         }
     }
+
+    /// Equality for any pair of values, including arrays and tuples
+    fn values_equal(&self, a: &DynSolValue, b: &DynSolValue) -> Result<bool> {
+        match (a, b) {
+            (
+                DynSolValue::Array(a_items) | DynSolValue::FixedArray(a_items) | DynSolValue::Tuple(a_items),
+                DynSolValue::Array(b_items) | DynSolValue::FixedArray(b_items) | DynSolValue::Tuple(b_items),
+            ) => {
+                if a_items.len() != b_items.len() {
+                    return Ok(false);
+                }
+                for (a_item, b_item) in a_items.iter().zip(b_items) {
+                    if !self.values_equal(a_item, b_item)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            _ => Ok(self.compare_values(a, b)? == 0),
+        }
+    }
+}
+
+/// Type of the values `contains` looks for in `value`
+fn contained_type(value: &DynSolValue) -> Option<DynSolType> {
+    match value {
+        DynSolValue::Array(_) | DynSolValue::FixedArray(_) => match value.as_type()? {
+            DynSolType::Array(inner) | DynSolType::FixedArray(inner, _) => Some(*inner),
+            _ => None,
+        },
+        DynSolValue::String(_) => Some(DynSolType::String),
+        DynSolValue::Bytes(_) | DynSolValue::FixedBytes(..) => Some(DynSolType::Bytes),
+        _ => None,
+    }
+}
+
+fn bytes_of(value: &DynSolValue) -> Option<Vec<u8>> {
+    match value {
+        DynSolValue::Bytes(bytes) => Some(bytes.clone()),
+        DynSolValue::FixedBytes(word, size) => Some(word[..*size].to_vec()),
+        _ => None,
+    }
+}
+
+/// Render a value the way it would be written in Solidity, e.g. for error messages
+pub fn format_sol_value(value: &DynSolValue) -> String {
+    match value {
+        DynSolValue::Bool(b) => b.to_string(),
+        DynSolValue::Int(i, _) => i.to_string(),
+        DynSolValue::Uint(u, _) => u.to_string(),
+        DynSolValue::FixedBytes(word, size) => format!("0x{}", hex::encode(&word[..*size])),
+        DynSolValue::Address(address) => address.to_checksum(None),
+        DynSolValue::Function(function) => format!("0x{}", hex::encode(function.as_slice())),
+        DynSolValue::Bytes(bytes) => format!("0x{}", hex::encode(bytes)),
+        DynSolValue::String(s) => format!("{:?}", s),
+        DynSolValue::Array(values) | DynSolValue::FixedArray(values) => {
+            let items: Vec<String> = values.iter().map(format_sol_value).collect();
+            format!("[{}]", items.join(", "))
+        }
+        DynSolValue::Tuple(values) => {
+            let items: Vec<String> = values.iter().map(format_sol_value).collect();
+            format!("({})", items.join(", "))
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
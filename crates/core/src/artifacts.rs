@@ -0,0 +1,377 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::{
+    data::ContractData,
+    errors::{DeployerError, Result},
+    linking::LinkReferences,
+};
+
+/// Compiler artifact layouts understood by `DataReference::Contract`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactFormat {
+    /// Foundry `out/<Source>.sol/<Contract>.json`
+    Foundry,
+    /// Hardhat `artifacts/<source>/<Contract>.json`
+    Hardhat,
+    /// solc standard-JSON output containing many contracts
+    StandardJson,
+    /// Minimal `{ "bytecode": "0x…", "abi": [...] }`
+    Plain,
+}
+
+impl ArtifactFormat {
+    /// Detect the artifact layout from its JSON shape
+    pub fn detect(json: &Value) -> Result<Self> {
+        if json.get("contracts").is_some_and(Value::is_object) {
+            return Ok(Self::StandardJson);
+        }
+
+        let hardhat_format = json
+            .get("_format")
+            .and_then(Value::as_str)
+            .is_some_and(|f| f.starts_with("hh-sol-artifact"));
+        if hardhat_format {
+            return Ok(Self::Hardhat);
+        }
+
+        match json.get("bytecode") {
+            Some(Value::Object(obj)) if obj.contains_key("object") => Ok(Self::Foundry),
+            Some(Value::String(_)) if json.get("linkReferences").is_some() => Ok(Self::Hardhat),
+            Some(Value::String(_)) => Ok(Self::Plain),
+            Some(_) => Err(DeployerError::Config(
+                "Invalid bytecode format in contract JSON".to_string(),
+            )),
+            None => Err(DeployerError::Config(
+                "No bytecode field in contract JSON".to_string(),
+            )),
+        }
+    }
+}
+
+/// Hash of the contract metadata embedded in the CBOR trailer of the bytecode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataHash {
+    /// `ipfs`, `bzzr0` or `bzzr1`
+    pub kind: String,
+    /// Hex encoded digest
+    pub digest: String,
+    /// Compiler version recorded next to the hash, if any
+    pub solc: Option<String>,
+}
+
+/// Parse a compiler artifact into `ContractData`.
+///
+/// `selector` picks a contract out of a standard-JSON output as
+/// `source:ContractName` (or just `ContractName` when unambiguous).
+pub fn parse_contract_artifact(
+    json: &Value,
+    default_name: &str,
+    selector: Option<&str>,
+) -> Result<ContractData> {
+    match ArtifactFormat::detect(json)? {
+        ArtifactFormat::Foundry => {
+            let bytecode = string_at(json, &["bytecode", "object"])?;
+            let deployed_bytecode = string_at(json, &["deployedBytecode", "object"]).ok();
+            let link_references = link_references_at(json, &["bytecode", "linkReferences"])?;
+            let metadata = metadata_object(json.get("metadata").or_else(|| json.get("rawMetadata")));
+
+            contract_data(
+                default_name.to_string(),
+                None,
+                ArtifactFormat::Foundry,
+                json,
+                bytecode,
+                deployed_bytecode,
+                link_references,
+                metadata,
+            )
+        }
+        ArtifactFormat::Hardhat => {
+            let bytecode = string_at(json, &["bytecode"])?;
+            let deployed_bytecode = string_at(json, &["deployedBytecode"]).ok();
+            let link_references = link_references_at(json, &["linkReferences"])?;
+            let name = json
+                .get("contractName")
+                .and_then(Value::as_str)
+                .unwrap_or(default_name)
+                .to_string();
+            let source = json.get("sourceName").and_then(Value::as_str).map(String::from);
+
+            contract_data(
+                name,
+                source,
+                ArtifactFormat::Hardhat,
+                json,
+                bytecode,
+                deployed_bytecode,
+                link_references,
+                HashMap::new(),
+            )
+        }
+        ArtifactFormat::StandardJson => {
+            let (source, name, contract) = select_contract(json, selector)?;
+            let bytecode = string_at(contract, &["evm", "bytecode", "object"])?;
+            let deployed_bytecode = string_at(contract, &["evm", "deployedBytecode", "object"]).ok();
+            let link_references =
+                link_references_at(contract, &["evm", "bytecode", "linkReferences"])?;
+            let metadata = metadata_object(contract.get("metadata"));
+
+            contract_data(
+                name,
+                Some(source),
+                ArtifactFormat::StandardJson,
+                contract,
+                bytecode,
+                deployed_bytecode,
+                link_references,
+                metadata,
+            )
+        }
+        ArtifactFormat::Plain => {
+            let bytecode = string_at(json, &["bytecode"])?;
+            let deployed_bytecode = string_at(json, &["deployedBytecode"]).ok();
+            let name = json
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or(default_name)
+                .to_string();
+
+            contract_data(
+                name,
+                None,
+                ArtifactFormat::Plain,
+                json,
+                bytecode,
+                deployed_bytecode,
+                LinkReferences::new(),
+                HashMap::new(),
+            )
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn contract_data(
+    name: String,
+    source: Option<String>,
+    format: ArtifactFormat,
+    artifact: &Value,
+    bytecode: String,
+    deployed_bytecode: Option<String>,
+    link_references: LinkReferences,
+    metadata: HashMap<String, Value>,
+) -> Result<ContractData> {
+    let abi = artifact
+        .get("abi")
+        .ok_or_else(|| DeployerError::Config("No abi field in contract JSON".to_string()))?
+        .clone();
+
+    let metadata_hash = deployed_bytecode
+        .as_deref()
+        .and_then(parse_metadata_hash)
+        .or_else(|| parse_metadata_hash(&bytecode));
+
+    let compiler_version = metadata
+        .get("compiler")
+        .and_then(|c| c.get("version"))
+        .and_then(Value::as_str)
+        .map(String::from);
+
+    // Foundry artifacts only record the source path in the metadata
+    let source = source.or_else(|| {
+        metadata
+            .get("settings")
+            .and_then(|s| s.get("compilationTarget"))
+            .and_then(Value::as_object)
+            .and_then(|target| target.keys().next().cloned())
+    });
+
+    Ok(ContractData {
+        name,
+        bytecode: with_hex_prefix(bytecode),
+        abi,
+        constructor: None,
+        link_references,
+        metadata,
+        source,
+        format: Some(format),
+        deployed_bytecode: deployed_bytecode.map(with_hex_prefix),
+        metadata_hash,
+        compiler_version,
+        standard_json_input: None,
+        artifact: artifact.clone(),
+    })
+}
+
+/// Find a contract in a standard-JSON output by `source:Name` or unique `Name`
+fn select_contract<'a>(
+    json: &'a Value,
+    selector: Option<&str>,
+) -> Result<(String, String, &'a Value)> {
+    let contracts = json
+        .get("contracts")
+        .and_then(Value::as_object)
+        .ok_or_else(|| DeployerError::Config("No contracts in standard-JSON output".to_string()))?;
+
+    let (source_filter, name_filter) = match selector.map(|s| s.rsplit_once(':')) {
+        Some(Some((source, name))) => (Some(source), Some(name)),
+        Some(None) => (None, selector),
+        None => (None, None),
+    };
+
+    let mut matches = Vec::new();
+    for (source, by_name) in contracts {
+        if source_filter.is_some_and(|f| f != source) {
+            continue;
+        }
+        for (name, contract) in by_name.as_object().into_iter().flatten() {
+            if name_filter.is_none() || name_filter == Some(name.as_str()) {
+                matches.push((source.clone(), name.clone(), contract));
+            }
+        }
+    }
+
+    match matches.len() {
+        1 => Ok(matches.remove(0)),
+        0 => Err(DeployerError::Config(format!(
+            "Contract {} not found in standard-JSON output",
+            selector.unwrap_or("<unspecified>")
+        ))),
+        _ => {
+            let candidates: Vec<String> =
+                matches.iter().map(|(source, name, _)| format!("{}:{}", source, name)).collect();
+            Err(DeployerError::Config(format!(
+                "Ambiguous contract selection, specify `name` as one of: {}",
+                candidates.join(", ")
+            )))
+        }
+    }
+}
+
+fn string_at(json: &Value, path: &[&str]) -> Result<String> {
+    path.iter()
+        .try_fold(json, |value, key| value.get(key))
+        .and_then(Value::as_str)
+        .map(String::from)
+        .ok_or_else(|| DeployerError::Config(format!("{} must be a string", path.join("."))))
+}
+
+fn link_references_at(json: &Value, path: &[&str]) -> Result<LinkReferences> {
+    match path.iter().try_fold(json, |value, key| value.get(key)) {
+        Some(refs) => serde_json::from_value(refs.clone()).map_err(|e| {
+            DeployerError::Config(format!("Invalid {}: {}", path.join("."), e))
+        }),
+        None => Ok(LinkReferences::new()),
+    }
+}
+
+/// Metadata is an object in Foundry artifacts but a JSON string elsewhere
+fn metadata_object(value: Option<&Value>) -> HashMap<String, Value> {
+    let value = match value {
+        Some(Value::String(s)) => serde_json::from_str(s).ok(),
+        Some(v) => Some(v.clone()),
+        None => None,
+    };
+    value
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn with_hex_prefix(code: String) -> String {
+    if code.starts_with("0x") {
+        code
+    } else {
+        format!("0x{}", code)
+    }
+}
+
+/// Decode the CBOR metadata trailer solc appends to bytecode
+pub fn parse_metadata_hash(bytecode: &str) -> Option<MetadataHash> {
+    let code = bytecode.trim_start_matches("0x");
+    if code.len() < 4 || !code.is_char_boundary(code.len() - 4) {
+        return None;
+    }
+    let length = usize::from_str_radix(&code[code.len() - 4..], 16).ok()?;
+    let end = code.len() - 4;
+    let start = end.checked_sub(length * 2)?;
+    let cbor = hex::decode(code.get(start..end)?).ok()?;
+
+    let entries = decode_cbor_map(&cbor)?;
+    let (kind, digest) = ["ipfs", "bzzr1", "bzzr0"]
+        .iter()
+        .find_map(|kind| entries.get(*kind).map(|digest| (kind.to_string(), digest)))?;
+    let digest = match digest {
+        CborValue::Bytes(bytes) => hex::encode(bytes),
+        CborValue::Text(_) => return None,
+    };
+    let solc = entries.get("solc").map(|version| match version {
+        CborValue::Bytes(bytes) => bytes.iter().map(u8::to_string).collect::<Vec<_>>().join("."),
+        CborValue::Text(text) => text.clone(),
+    });
+
+    Some(MetadataHash { kind, digest, solc })
+}
+
+enum CborValue {
+    Bytes(Vec<u8>),
+    Text(String),
+}
+
+/// Minimal decoder for the flat text-keyed map solc emits
+fn decode_cbor_map(data: &[u8]) -> Option<HashMap<String, CborValue>> {
+    let (&header, mut rest) = data.split_first()?;
+    if header & 0xe0 != 0xa0 {
+        return None;
+    }
+
+    let mut entries = HashMap::new();
+    for _ in 0..(header & 0x1f) {
+        let (key_type, key, remaining) = decode_cbor_item(rest)?;
+        if key_type != 3 {
+            return None;
+        }
+        let key = String::from_utf8(key).ok()?;
+        let (value_type, value, remaining) = decode_cbor_item(remaining)?;
+        rest = remaining;
+
+        match value_type {
+            2 => {
+                entries.insert(key, CborValue::Bytes(value));
+            }
+            3 => {
+                entries.insert(key, CborValue::Text(String::from_utf8(value).ok()?));
+            }
+            // Simple values such as the `experimental` flag carry no payload
+            _ => {}
+        }
+    }
+    Some(entries)
+}
+
+fn decode_cbor_item(data: &[u8]) -> Option<(u8, Vec<u8>, &[u8])> {
+    let (&header, rest) = data.split_first()?;
+    let major = header >> 5;
+    let info = header & 0x1f;
+
+    if major == 7 {
+        return Some((major, Vec::new(), rest));
+    }
+
+    let (length, rest) = match info {
+        0..=23 => (info as usize, rest),
+        24 => (*rest.first()? as usize, &rest[1..]),
+        25 => (u16::from_be_bytes([*rest.first()?, *rest.get(1)?]) as usize, &rest[2..]),
+        _ => return None,
+    };
+
+    match major {
+        2 | 3 => {
+            let payload = rest.get(..length)?.to_vec();
+            Some((major, payload, &rest[length..]))
+        }
+        _ => None,
+    }
+}
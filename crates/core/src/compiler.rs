@@ -0,0 +1,263 @@
+use alloy::primitives::keccak256;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::errors::{DeployerError, Result};
+
+/// Compiler settings for a `DataReference::Source`
+///
+/// Optimizer settings are pinned by default so repeated compilations produce
+/// identical bytecode and therefore identical CREATE2 addresses.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CompilerSettings {
+    /// Path to the `solc` binary
+    #[serde(default = "default_solc")]
+    pub solc: String,
+    /// Required compiler version, e.g. `0.8.23`
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default = "default_optimizer")]
+    pub optimizer: bool,
+    #[serde(default = "default_optimizer_runs")]
+    pub optimizer_runs: u32,
+    #[serde(default)]
+    pub evm_version: Option<String>,
+    #[serde(default)]
+    pub via_ir: bool,
+}
+
+fn default_solc() -> String {
+    "solc".to_string()
+}
+
+fn default_optimizer() -> bool {
+    true
+}
+
+fn default_optimizer_runs() -> u32 {
+    200
+}
+
+/// The `major.minor.patch` part of a solc version, e.g. `0.8.23` for
+/// `0.8.23+commit.f704f362.Linux.g++`
+fn release_version(version: &str) -> &str {
+    let version = version.trim().trim_start_matches('v');
+    version.split_once('+').map_or(version, |(release, _)| release)
+}
+
+impl Default for CompilerSettings {
+    fn default() -> Self {
+        Self {
+            solc: default_solc(),
+            version: None,
+            optimizer: default_optimizer(),
+            optimizer_runs: default_optimizer_runs(),
+            evm_version: None,
+            via_ir: false,
+        }
+    }
+}
+
+/// A cached compilation: the standard-JSON input and output plus the hashes of
+/// every source file that went into it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompilationOutput {
+    pub compiler_version: String,
+    pub input: Value,
+    pub output: Value,
+    pub source_hashes: BTreeMap<String, String>,
+}
+
+/// Compiles Solidity sources with a locally installed `solc`, caching the
+/// results on disk
+#[derive(Debug)]
+pub struct SolcCompiler {
+    base_path: PathBuf,
+    cache_dir: PathBuf,
+}
+
+impl SolcCompiler {
+    pub fn new(base_path: impl Into<PathBuf>, cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_path: base_path.into(),
+            cache_dir: cache_dir.into(),
+        }
+    }
+
+    /// Compile `source` (relative to the base path), reusing a cached result
+    /// when the compiler, settings and all involved sources are unchanged
+    pub fn compile(&self, source: &str, settings: &CompilerSettings) -> Result<CompilationOutput> {
+        let compiler_version = self.compiler_version(settings)?;
+        let content = std::fs::read_to_string(self.base_path.join(source)).map_err(|e| {
+            DeployerError::Config(format!("Failed to read source {}: {}", source, e))
+        })?;
+        let input = standard_json_input(source, &content, settings);
+
+        let cache_key = keccak256(
+            serde_json::to_vec(&json!([compiler_version, input]))
+                .map_err(|e| DeployerError::Serialization(e.to_string()))?,
+        );
+        let cache_path = self.cache_dir.join(format!("{:x}.json", cache_key));
+
+        if let Some(cached) = self.load_cached(&cache_path) {
+            return Ok(cached);
+        }
+
+        let output = self.run_solc(settings, &input)?;
+        let source_hashes = output
+            .get("sources")
+            .and_then(Value::as_object)
+            .map(|sources| {
+                sources
+                    .keys()
+                    .filter_map(|path| Some((path.clone(), self.hash_source(path)?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let compiled = CompilationOutput {
+            compiler_version,
+            input,
+            output,
+            source_hashes,
+        };
+        self.store_cached(&cache_path, &compiled)?;
+        Ok(compiled)
+    }
+
+    fn compiler_version(&self, settings: &CompilerSettings) -> Result<String> {
+        let output = Command::new(&settings.solc)
+            .arg("--version")
+            .output()
+            .map_err(|e| DeployerError::Config(format!("Failed to run {}: {}", settings.solc, e)))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let version = stdout
+            .lines()
+            .find_map(|line| line.strip_prefix("Version: "))
+            .map(|v| v.trim().to_string())
+            .ok_or_else(|| {
+                DeployerError::Config(format!("Could not determine version of {}", settings.solc))
+            })?;
+
+        if let Some(required) = &settings.version {
+            if release_version(&version) != release_version(required) {
+                return Err(DeployerError::Config(format!(
+                    "{} is version {}, but {} is required",
+                    settings.solc, version, required
+                )));
+            }
+        }
+
+        Ok(version)
+    }
+
+    fn run_solc(&self, settings: &CompilerSettings, input: &Value) -> Result<Value> {
+        let mut child = Command::new(&settings.solc)
+            .arg("--standard-json")
+            .arg("--base-path")
+            .arg(&self.base_path)
+            .arg("--allow-paths")
+            .arg(&self.base_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| DeployerError::Config(format!("Failed to run {}: {}", settings.solc, e)))?;
+
+        let stdin_bytes = serde_json::to_vec(input)
+            .map_err(|e| DeployerError::Serialization(e.to_string()))?;
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| DeployerError::Config("Failed to open solc stdin".to_string()))?
+            .write_all(&stdin_bytes)?;
+
+        let result = child.wait_with_output()?;
+        let output: Value = serde_json::from_slice(&result.stdout).map_err(|e| {
+            DeployerError::Config(format!(
+                "Invalid solc output: {} {}",
+                e,
+                String::from_utf8_lossy(&result.stderr)
+            ))
+        })?;
+
+        let errors: Vec<&str> = output
+            .get("errors")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|e| e.get("severity").and_then(Value::as_str) == Some("error"))
+            .filter_map(|e| {
+                e.get("formattedMessage")
+                    .or_else(|| e.get("message"))
+                    .and_then(Value::as_str)
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(DeployerError::Config(format!(
+                "Compilation failed:\n{}",
+                errors.join("\n")
+            )));
+        }
+
+        Ok(output)
+    }
+
+    fn load_cached(&self, cache_path: &Path) -> Option<CompilationOutput> {
+        let content = std::fs::read_to_string(cache_path).ok()?;
+        let cached: CompilationOutput = serde_json::from_str(&content).ok()?;
+
+        // Imported files are not part of the cache key, so check them here
+        let fresh = cached
+            .source_hashes
+            .iter()
+            .all(|(path, hash)| self.hash_source(path).as_ref() == Some(hash));
+        fresh.then_some(cached)
+    }
+
+    fn store_cached(&self, cache_path: &Path, compiled: &CompilationOutput) -> Result<()> {
+        std::fs::create_dir_all(&self.cache_dir)?;
+        let content = serde_json::to_string(compiled)
+            .map_err(|e| DeployerError::Serialization(e.to_string()))?;
+        std::fs::write(cache_path, content)?;
+        Ok(())
+    }
+
+    fn hash_source(&self, path: &str) -> Option<String> {
+        let content = std::fs::read(self.base_path.join(path)).ok()?;
+        Some(format!("{:x}", keccak256(content)))
+    }
+}
+
+/// Build the solc standard-JSON input for a single entry source
+pub fn standard_json_input(source: &str, content: &str, settings: &CompilerSettings) -> Value {
+    let mut compiler_settings = json!({
+        "optimizer": {
+            "enabled": settings.optimizer,
+            "runs": settings.optimizer_runs,
+        },
+        "viaIR": settings.via_ir,
+        "outputSelection": {
+            "*": {
+                "*": ["abi", "evm.bytecode", "evm.deployedBytecode", "metadata", "storageLayout"],
+            },
+        },
+    });
+    if let Some(evm_version) = &settings.evm_version {
+        compiler_settings["evmVersion"] = json!(evm_version);
+    }
+
+    json!({
+        "language": "Solidity",
+        "sources": {
+            source: { "content": content },
+        },
+        "settings": compiler_settings,
+    })
+}
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Mutex;
+use crate::artifacts::{parse_contract_artifact, ArtifactFormat, MetadataHash};
+use crate::compiler::{CompilationOutput, CompilerSettings, SolcCompiler};
 use crate::errors::{DeployerError, Result};
+use crate::linking::LinkReferences;
+use crate::secrets::{Secret, SecretStore};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContractData {
@@ -8,8 +13,40 @@ pub struct ContractData {
     pub bytecode: String,
     pub abi: serde_json::Value,
     pub constructor: Option<String>,
+    /// Library placeholders in `bytecode` that must be linked before deployment
+    #[serde(default)]
+    pub link_references: LinkReferences,
     #[serde(default)]
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Source file the contract was compiled from, when the artifact records it
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Layout of the artifact the contract was loaded from
+    #[serde(default)]
+    pub format: Option<ArtifactFormat>,
+    /// Runtime bytecode, if the artifact includes it
+    #[serde(default)]
+    pub deployed_bytecode: Option<String>,
+    /// Metadata hash embedded in the bytecode
+    #[serde(default)]
+    pub metadata_hash: Option<MetadataHash>,
+    /// Full compiler version, e.g. `0.8.23+commit.f704f362`
+    #[serde(default)]
+    pub compiler_version: Option<String>,
+    /// Standard-JSON input the contract was compiled from, when known
+    #[serde(skip)]
+    pub standard_json_input: Option<serde_json::Value>,
+    /// The contract's entry in the original artifact
+    #[serde(skip)]
+    pub artifact: serde_json::Value,
+}
+
+impl ContractData {
+    /// Parse the contract's JSON ABI
+    pub fn json_abi(&self) -> Result<alloy::json_abi::JsonAbi> {
+        serde_json::from_value(self.abi.clone())
+            .map_err(|e| DeployerError::AbiParsing(format!("Invalid ABI for {}: {}", self.name, e)))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,8 +59,25 @@ pub struct VariableData {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum DataReference {
-    /// Reference to a contract file: contracts/MyContract.json
-    Contract { path: String },
+    /// Reference to a contract artifact: contracts/MyContract.json
+    ///
+    /// Foundry, Hardhat and solc standard-JSON outputs are detected automatically;
+    /// `name` selects a contract from standard-JSON output as `source:ContractName`
+    Contract {
+        path: String,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    /// Reference to a Solidity source compiled on demand: contracts/src/MyContract.sol
+    ///
+    /// `name` selects the contract within the file and defaults to the file stem
+    Source {
+        path: String,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        compiler: CompilerSettings,
+    },
     /// Reference to shared variables: variables/mainnet.yml
     Variables { path: String },
     /// Reference to raw data: data/config.json
@@ -36,6 +90,10 @@ pub struct PipelineConfig {
     /// Local variables specific to this pipeline
     #[serde(default)]
     pub variables: HashMap<String, crate::Variable>,
+
+    /// Parameters supplied at run time
+    #[serde(default)]
+    pub inputs: HashMap<String, crate::InputDefinition>,
     
     /// References to external data sources (shared variables, contracts, etc.)
     #[serde(default)]
@@ -47,27 +105,60 @@ pub struct PipelineConfig {
     /// Optional metadata
     #[serde(default)]
     pub metadata: HashMap<String, serde_json::Value>,
+
+    /// Block explorer verification of deployed contracts
+    #[serde(default)]
+    pub verification: Option<crate::VerificationConfig>,
+
+    /// Safe multisig that `sender: safe` writes are batched for
+    #[serde(default)]
+    pub safe: Option<crate::SafeConfig>,
+
+    /// Batch reads through Multicall3
+    #[serde(default)]
+    pub read_batch: Option<crate::ReadBatchConfig>,
 }
 
 pub trait DataResolver {
-    fn get_contract_data(&self, path: &str) -> Result<ContractData>;
+    fn get_contract_data(&self, path: &str, name: Option<&str>) -> Result<ContractData>;
     fn get_variable_data(&self, path: &str) -> Result<VariableData>;
     fn get_raw_data(&self, path: &str) -> Result<serde_json::Value>;
     fn get_raw_contract_data(&self, path: &str) -> Result<serde_json::Value>;
+    fn get_source_data(
+        &self,
+        path: &str,
+        name: Option<&str>,
+        settings: &CompilerSettings,
+    ) -> Result<ContractData>;
+    fn get_secret(&self, name: &str) -> Result<Secret>;
 }
 
 #[derive(Debug)]
 pub struct FileDataResolver {
     data_dir: std::path::PathBuf,
+    secrets: SecretStore,
+    compiler: SolcCompiler,
+    /// Sources already compiled by this resolver, by path and compiler settings
+    compiled: Mutex<HashMap<(String, CompilerSettings), CompilationOutput>>,
 }
 
 impl FileDataResolver {
     pub fn new(data_dir: impl Into<std::path::PathBuf>) -> Self {
+        let data_dir = data_dir.into();
         Self {
-            data_dir: data_dir.into(),
+            compiler: SolcCompiler::new(&data_dir, data_dir.join(".cache").join("solc")),
+            data_dir,
+            secrets: SecretStore::default(),
+            compiled: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Use `secrets` to resolve `!secret` values
+    pub fn with_secrets(mut self, secrets: SecretStore) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
     pub fn resolve_path(&self, path: &str) -> Result<std::path::PathBuf> {
         let full_path = if path.ends_with(".json") || path.ends_with(".yml") || path.ends_with(".yaml") {
             self.data_dir.join(path)
@@ -95,6 +186,19 @@ impl FileDataResolver {
         Ok(full_path)
     }
 
+    /// Compile a source once per resolver; later lookups of any contract in it
+    /// reuse the output without running solc or re-hashing the sources
+    fn compile(&self, path: &str, settings: &CompilerSettings) -> Result<CompilationOutput> {
+        let key = (path.to_string(), settings.clone());
+        let mut compiled = self.compiled.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(output) = compiled.get(&key) {
+            return Ok(output.clone());
+        }
+        let output = self.compiler.compile(path, settings)?;
+        compiled.insert(key, output.clone());
+        Ok(output)
+    }
+
     pub fn load_file(&self, path: &std::path::Path) -> Result<serde_json::Value> {
         let content = std::fs::read_to_string(path)
             .map_err(|e| DeployerError::Config(format!("Failed to read file {}: {}", path.display(), e)))?;
@@ -115,42 +219,16 @@ impl FileDataResolver {
 }
 
 impl DataResolver for FileDataResolver {
-    fn get_contract_data(&self, path: &str) -> Result<ContractData> {
+    fn get_contract_data(&self, path: &str, name: Option<&str>) -> Result<ContractData> {
         let file_path = self.resolve_path(path)?;
         let json_value = self.load_file(&file_path)?;
-        
-        // Handle standard contract JSON format where bytecode is nested
-        let bytecode = if let Some(bytecode_obj) = json_value.get("bytecode") {
-            if let Some(bytecode_str) = bytecode_obj.get("object") {
-                bytecode_str.as_str()
-                    .ok_or_else(|| DeployerError::Config("bytecode.object must be a string".to_string()))?
-                    .to_string()
-            } else if let Some(bytecode_str) = bytecode_obj.as_str() {
-                // Handle case where bytecode is directly a string
-                bytecode_str.to_string()
-            } else {
-                return Err(DeployerError::Config("Invalid bytecode format in contract JSON".to_string()));
-            }
-        } else {
-            return Err(DeployerError::Config("No bytecode field in contract JSON".to_string()));
-        };
-        
-        let abi = json_value.get("abi")
-            .ok_or_else(|| DeployerError::Config("No abi field in contract JSON".to_string()))?
-            .clone();
-            
-        let name = file_path.file_stem()
+
+        let default_name = file_path.file_stem()
             .and_then(|s| s.to_str())
-            .unwrap_or("Unknown")
-            .to_string();
-            
-        Ok(ContractData {
-            name,
-            bytecode,
-            abi,
-            constructor: None,
-            metadata: HashMap::new(),
-        })
+            .unwrap_or("Unknown");
+
+        parse_contract_artifact(&json_value, default_name, name)
+            .map_err(|e| DeployerError::Config(format!("Invalid contract artifact {}: {}", path, e)))
     }
 
     fn get_variable_data(&self, path: &str) -> Result<VariableData> {
@@ -170,6 +248,30 @@ impl DataResolver for FileDataResolver {
         // Same as get_raw_data for file-based resolver
         self.get_raw_data(path)
     }
+
+    fn get_source_data(
+        &self,
+        path: &str,
+        name: Option<&str>,
+        settings: &CompilerSettings,
+    ) -> Result<ContractData> {
+        let compiled = self.compile(path, settings)?;
+
+        let stem = std::path::Path::new(path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Unknown");
+        let selector = format!("{}:{}", path, name.unwrap_or(stem));
+
+        let mut contract = parse_contract_artifact(&compiled.output, stem, Some(&selector))?;
+        contract.compiler_version = Some(compiled.compiler_version);
+        contract.standard_json_input = Some(compiled.input);
+        Ok(contract)
+    }
+
+    fn get_secret(&self, name: &str) -> Result<Secret> {
+        self.secrets.get(name)
+    }
 }
 
 /// Variable resolver that handles both local and shared variables with proper precedence
@@ -226,32 +328,14 @@ impl<'a, R: DataResolver> crate::VariableResolver for HierarchicalVariableResolv
     fn get_variable(&self, key: &str) -> Result<alloy::dyn_abi::DynSolValue> {
         // 1. Check local variables first (highest precedence)
         if let Some(var) = self.local_variables.get(key) {
-            let sol_type = alloy::dyn_abi::DynSolType::parse(&var.ty)
-                .map_err(|_e| DeployerError::TypeConversion {
-                    expected: var.ty.clone(),
-                    actual: format!("parse error"),
-                })?;
-            return sol_type.coerce_str(&var.value)
-                .map_err(|_e| DeployerError::TypeConversion {
-                    expected: var.ty.clone(),
-                    actual: var.value.clone(),
-                });
+            return var.resolve(self);
         }
 
         // 2. Check shared variables from data references
         for (data_ref_key, _) in self.data_refs.iter() {
             if let Ok(shared_vars) = self.load_shared_variables(data_ref_key) {
                 if let Some(var) = shared_vars.variables.get(key) {
-                    let sol_type = alloy::dyn_abi::DynSolType::parse(&var.ty)
-                        .map_err(|_e| DeployerError::TypeConversion {
-                            expected: var.ty.clone(),
-                            actual: format!("parse error"),
-                        })?;
-                    return sol_type.coerce_str(&var.value)
-                        .map_err(|_e| DeployerError::TypeConversion {
-                            expected: var.ty.clone(),
-                            actual: var.value.clone(),
-                        });
+                    return var.resolve(self);
                 }
             }
         }
@@ -259,6 +343,19 @@ impl<'a, R: DataResolver> crate::VariableResolver for HierarchicalVariableResolv
         Err(DeployerError::VariableNotFound(key.to_string()))
     }
 
+    fn is_secret_variable(&self, key: &str) -> bool {
+        if let Some(var) = self.local_variables.get(key) {
+            return var.value.contains_secret();
+        }
+
+        self.data_refs.keys().any(|data_ref_key| {
+            self.load_shared_variables(data_ref_key)
+                .ok()
+                .and_then(|shared_vars| shared_vars.variables.get(key).cloned())
+                .is_some_and(|var| var.value.contains_secret())
+        })
+    }
+
     fn get_output(&self, id: &str) -> Result<alloy::dyn_abi::DynSolValue> {
         // Output resolution would be handled by the indexer
         // This is just a placeholder implementation
@@ -274,29 +371,23 @@ impl<'a, R: DataResolver> crate::VariableResolver for HierarchicalVariableResolv
             .ok_or_else(|| DeployerError::Config(format!("Data reference not found: {}", data_ref_key)))?;
 
         match data_ref {
-            DataReference::Contract { path: contract_path } => {
-                // Get the raw contract JSON
-                let contract_json = self.data_resolver.get_raw_contract_data(contract_path)?;
-                
-                // Navigate through the JSON using the field path
-                let value = navigate_json_path(&contract_json, field_path)?;
-                
+            DataReference::Contract { .. } | DataReference::Source { .. } => {
+                let contract = self.get_contract(data_ref_key)?;
+
+                // Prefer the normalized fields (e.g. `deployed_bytecode`), falling back
+                // to the artifact's own layout (e.g. `bytecode.object`)
+                let normalized = serde_json::to_value(&contract)
+                    .map_err(|e| DeployerError::Serialization(e.to_string()))?;
+                let value = navigate_json_path(&normalized, field_path)
+                    .or_else(|_| navigate_json_path(&contract.artifact, field_path))?;
+
                 // Convert JSON value to DynSolValue
                 json_to_dyn_sol_value(value)
             }
             DataReference::Variables { path: vars_path } => {
                 let var_data = self.data_resolver.get_variable_data(vars_path)?;
                 if let Some(var) = var_data.variables.get(field_path) {
-                    let sol_type = alloy::dyn_abi::DynSolType::parse(&var.ty)
-                        .map_err(|_e| DeployerError::TypeConversion {
-                            expected: var.ty.clone(),
-                            actual: format!("parse error"),
-                        })?;
-                    sol_type.coerce_str(&var.value)
-                        .map_err(|_e| DeployerError::TypeConversion {
-                            expected: var.ty.clone(),
-                            actual: var.value.clone(),
-                        })
+                    var.resolve(self)
                 } else {
                     Err(DeployerError::VariableNotFound(field_path.to_string()))
                 }
@@ -308,6 +399,25 @@ impl<'a, R: DataResolver> crate::VariableResolver for HierarchicalVariableResolv
             }
         }
     }
+
+    fn get_contract(&self, key: &str) -> Result<ContractData> {
+        let data_ref = self.data_refs.get(key)
+            .ok_or_else(|| DeployerError::Config(format!("Data reference not found: {}", key)))?;
+
+        match data_ref {
+            DataReference::Contract { path, name } => {
+                self.data_resolver.get_contract_data(path, name.as_deref())
+            }
+            DataReference::Source { path, name, compiler } => {
+                self.data_resolver.get_source_data(path, name.as_deref(), compiler)
+            }
+            _ => Err(DeployerError::Config(format!("Data reference '{}' is not a contract type", key))),
+        }
+    }
+
+    fn get_secret(&self, name: &str) -> Result<Secret> {
+        self.data_resolver.get_secret(name)
+    }
 }
 
 /// Navigate through a JSON value using a dot-separated path
@@ -357,19 +467,18 @@ fn json_to_dyn_sol_value(value: &serde_json::Value) -> Result<alloy::dyn_abi::Dy
         serde_json::Value::String(s) => {
             // Try to detect the appropriate type based on the string content
             // First check if it's a hex string that could be bytes or address
-            if s.starts_with("0x") {
-                let hex_data = &s[2..];
+            if let Some(hex_data) = s.strip_prefix("0x") {
                 // Check if it's a valid address (40 hex chars)
                 if hex_data.len() == 40 {
-                    match alloy::dyn_abi::DynSolType::Address.coerce_str(s) {
-                        Ok(val) => return Ok(val),
-                        Err(_) => {} // Fall through to try as bytes
+                    // Fall through to try as bytes on failure
+                    if let Ok(val) = alloy::dyn_abi::DynSolType::Address.coerce_str(s) {
+                        return Ok(val);
                     }
                 }
                 // Try as bytes
-                match alloy::dyn_abi::DynSolType::Bytes.coerce_str(s) {
-                    Ok(val) => return Ok(val),
-                    Err(_) => {} // Fall through to string
+                // Fall through to string on failure
+                if let Ok(val) = alloy::dyn_abi::DynSolType::Bytes.coerce_str(s) {
+                    return Ok(val);
                 }
             }
             // Default to string
@@ -397,8 +506,9 @@ fn json_to_dyn_sol_value(value: &serde_json::Value) -> Result<alloy::dyn_abi::Dy
             // For objects, check if it has special structure (like bytecode object)
             if let Some(bytecode_obj) = obj.get("object") {
                 if let Some(bytecode_str) = bytecode_obj.as_str() {
-                    return alloy::dyn_abi::DynSolType::Bytes.coerce_str(bytecode_str)
-                        .map_err(|_| DeployerError::Config("Failed to convert bytecode object".to_string()));
+                    // Unlinked bytecode contains placeholders, so keep it as a string
+                    return Ok(alloy::dyn_abi::DynSolType::Bytes.coerce_str(bytecode_str)
+                        .unwrap_or_else(|_| alloy::dyn_abi::DynSolValue::String(bytecode_str.to_string())));
                 }
             }
             
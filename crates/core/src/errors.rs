@@ -23,6 +23,15 @@ pub enum DeployerError {
     #[error("Transaction failed: {0}")]
     TransactionFailed(String),
     
+    #[error("Incompatible storage layout: {0}")]
+    StorageLayout(String),
+    
+    #[error("Assertion failed: {0}")]
+    AssertionFailed(String),
+    
+    #[error("Expression error: {0}")]
+    Expression(String),
+    
     #[error("ABI parsing error: {0}")]
     AbiParsing(String),
     
@@ -0,0 +1,715 @@
+use alloy::{
+    dyn_abi::{DynSolType, DynSolValue},
+    primitives::{keccak256, Address, B256, U256},
+};
+
+use crate::{
+    errors::{DeployerError, Result},
+    variables::VariableResolver,
+};
+
+/// Expressions are evaluated without side effects; these limits keep a
+/// malicious config from exhausting memory or the stack
+const MAX_LENGTH: usize = 4096;
+const MAX_DEPTH: usize = 32;
+
+/// Evaluate an `!expr` value such as `stake_amount * 2`, `1.5 ether` or
+/// `keccak256(abi.encodePacked(name))`.
+///
+/// Bare identifiers are variables; `var(..)`, `output(..)` and `data(..)`
+/// reference the other value sources explicitly. Arithmetic is on uint256
+/// and fails on overflow, underflow and division by zero. `+` also
+/// concatenates strings and bytes.
+pub fn evaluate<R: VariableResolver>(expr: &str, resolver: &R) -> Result<DynSolValue> {
+    if expr.len() > MAX_LENGTH {
+        return Err(expression_error(format!(
+            "Expression exceeds {} characters",
+            MAX_LENGTH
+        )));
+    }
+
+    let mut evaluator = Evaluator {
+        tokens: tokenize(expr)?,
+        pos: 0,
+        depth: 0,
+        resolver,
+    };
+    let value = evaluator.expr()?;
+    if let Some(token) = evaluator.peek() {
+        return Err(expression_error(format!("Unexpected {:?} in {}", token, expr)));
+    }
+    Ok(value)
+}
+
+/// Evaluate an expression and convert the result to the expected type
+pub fn evaluate_as<R: VariableResolver>(
+    expr: &str,
+    expected_type: &DynSolType,
+    resolver: &R,
+) -> Result<DynSolValue> {
+    convert_value(evaluate(expr, resolver)?, expected_type)
+}
+
+/// Names the expression may look up as variables, e.g. to tell whether its
+/// result is derived from a secret. String literals are only included as the
+/// key of `var('..')`
+pub fn referenced_names(expr: &str) -> Vec<String> {
+    let tokens = tokenize(expr).unwrap_or_default();
+    tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(i, token)| match token {
+            Token::Ident(name) => Some(name.clone()),
+            Token::Str(key)
+                if i >= 2 && matches!(&tokens[i - 2..i], [Token::Ident(f), Token::LParen] if f == "var") =>
+            {
+                Some(key.clone())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Convert a value to `expected_type` where the conversion is lossless,
+/// e.g. a uint256 result into a uint64 parameter
+pub fn convert_value(value: DynSolValue, expected_type: &DynSolType) -> Result<DynSolValue> {
+    if expected_type.matches(&value) {
+        return Ok(value);
+    }
+
+    let converted = match (&value, expected_type) {
+        (DynSolValue::Uint(v, _), DynSolType::Uint(bits)) if v.bit_len() <= *bits => {
+            Some(DynSolValue::Uint(*v, *bits))
+        }
+        (DynSolValue::Uint(v, _), DynSolType::FixedBytes(32)) => {
+            Some(DynSolValue::FixedBytes(B256::from(*v), 32))
+        }
+        (DynSolValue::Uint(v, _), DynSolType::Address) if v.bit_len() <= 160 => {
+            Some(DynSolValue::Address(Address::from_word(B256::from(*v))))
+        }
+        (DynSolValue::String(s), _) => expected_type.coerce_str(s).ok(),
+        (_, DynSolType::Bytes) => bytes_of(&value, false).map(DynSolValue::Bytes),
+        (_, DynSolType::FixedBytes(size)) => bytes_of(&value, false)
+            .filter(|bytes| bytes.len() == *size)
+            .map(|bytes| DynSolValue::FixedBytes(B256::right_padding_from(&bytes), *size)),
+        (_, DynSolType::Address) => bytes_of(&value, false)
+            .filter(|bytes| bytes.len() == 20)
+            .map(|bytes| DynSolValue::Address(Address::from_slice(&bytes))),
+        _ => None,
+    };
+
+    converted.ok_or_else(|| DeployerError::TypeConversion {
+        expected: expected_type.sol_type_name().into_owned(),
+        actual: type_name(&value),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    Str(String),
+    Hex(String),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    StarStar,
+    Slash,
+    Percent,
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    let take_while = |start: usize, pred: &dyn Fn(char) -> bool| {
+        let mut end = start;
+        while end < chars.len() && pred(chars[end]) {
+            end += 1;
+        }
+        (chars[start..end].iter().collect::<String>(), end)
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '0' if matches!(chars.get(i + 1), Some('x') | Some('X')) => {
+                let (digits, end) = take_while(i + 2, &|c| c.is_ascii_hexdigit());
+                tokens.push(Token::Hex(digits));
+                i = end;
+            }
+            c if c.is_ascii_digit() => {
+                let (number, end) = take_while(i, &|c| c.is_ascii_digit() || c == '.' || c == '_');
+                tokens.push(Token::Number(number.replace('_', "")));
+                i = end;
+            }
+            '"' | '\'' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(expression_error("Unterminated string literal")),
+                        Some(&q) if q == c => break,
+                        Some('\\') => {
+                            match chars.get(i + 1) {
+                                Some('n') => value.push('\n'),
+                                Some('t') => value.push('\t'),
+                                Some(&escaped) => value.push(escaped),
+                                None => return Err(expression_error("Unterminated string literal")),
+                            }
+                            i += 1;
+                        }
+                        Some(&other) => value.push(other),
+                    }
+                    i += 1;
+                }
+                tokens.push(Token::Str(value));
+                i += 1;
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                let (ident, end) =
+                    take_while(i, &|c| c.is_alphanumeric() || c == '_' || c == '$' || c == '.');
+                tokens.push(Token::Ident(ident));
+                i = end;
+            }
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                tokens.push(Token::StarStar);
+                i += 2;
+            }
+            _ => {
+                let token = match c {
+                    '+' => Token::Plus,
+                    '-' => Token::Minus,
+                    '*' => Token::Star,
+                    '/' => Token::Slash,
+                    '%' => Token::Percent,
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    _ => return Err(expression_error(format!("Unexpected character '{}'", c))),
+                };
+                tokens.push(token);
+                i += 1;
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Evaluator<'a, R> {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+    resolver: &'a R,
+}
+
+impl<R: VariableResolver> Evaluator<'_, R> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            other => Err(expression_error(format!(
+                "Expected {:?}, found {:?}",
+                expected, other
+            ))),
+        }
+    }
+
+    fn expr(&mut self) -> Result<DynSolValue> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(expression_error("Expression nested too deeply"));
+        }
+
+        let mut lhs = self.term()?;
+        while let Some(op) = self.peek().cloned() {
+            lhs = match op {
+                Token::Plus => {
+                    self.pos += 1;
+                    add(lhs, self.term()?)?
+                }
+                Token::Minus => {
+                    self.pos += 1;
+                    let rhs = uint(&self.term()?)?;
+                    let value = uint(&lhs)?
+                        .checked_sub(rhs)
+                        .ok_or_else(|| expression_error("Arithmetic underflow"))?;
+                    DynSolValue::Uint(value, 256)
+                }
+                _ => break,
+            };
+        }
+
+        self.depth -= 1;
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<DynSolValue> {
+        let mut lhs = self.power()?;
+        while let Some(op) = self.peek().cloned() {
+            let apply: fn(U256, U256) -> Option<U256> = match op {
+                Token::Star => U256::checked_mul,
+                Token::Slash => U256::checked_div,
+                Token::Percent => U256::checked_rem,
+                _ => break,
+            };
+            self.pos += 1;
+            let rhs = uint(&self.power()?)?;
+            let value = apply(uint(&lhs)?, rhs).ok_or_else(|| {
+                expression_error(match op {
+                    Token::Star => "Arithmetic overflow",
+                    _ => "Division by zero",
+                })
+            })?;
+            lhs = DynSolValue::Uint(value, 256);
+        }
+        Ok(lhs)
+    }
+
+    fn power(&mut self) -> Result<DynSolValue> {
+        let base = self.primary()?;
+        if self.peek() != Some(&Token::StarStar) {
+            return Ok(base);
+        }
+        self.pos += 1;
+        let exponent = uint(&self.power()?)?;
+        let value = uint(&base)?
+            .checked_pow(exponent)
+            .ok_or_else(|| expression_error("Arithmetic overflow"))?;
+        Ok(DynSolValue::Uint(value, 256))
+    }
+
+    fn primary(&mut self) -> Result<DynSolValue> {
+        match self.next() {
+            Some(Token::Number(number)) => {
+                let unit = match self.peek() {
+                    Some(Token::Ident(name)) => unit_multiplier(name),
+                    _ => None,
+                };
+                if unit.is_some() {
+                    self.pos += 1;
+                }
+                parse_number(&number, unit).map(|v| DynSolValue::Uint(v, 256))
+            }
+            Some(Token::Str(value)) => Ok(DynSolValue::String(value)),
+            Some(Token::Hex(digits)) => {
+                let bytes = hex::decode(&digits)?;
+                // Like Solidity, 20-byte hex literals are addresses
+                if bytes.len() == 20 {
+                    Ok(DynSolValue::Address(Address::from_slice(&bytes)))
+                } else {
+                    Ok(DynSolValue::Bytes(bytes))
+                }
+            }
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::LParen) => self.call(&name),
+            Some(Token::Ident(name)) => self.resolver.get_variable(&name),
+            Some(Token::LParen) => {
+                let value = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(value)
+            }
+            Some(token) => Err(expression_error(format!("Unexpected {:?}", token))),
+            None => Err(expression_error("Unexpected end of expression")),
+        }
+    }
+
+    fn call(&mut self, name: &str) -> Result<DynSolValue> {
+        self.expect(Token::LParen)?;
+
+        // References take a raw key rather than an evaluated argument
+        if matches!(name, "var" | "output" | "data") {
+            let key = match self.next() {
+                Some(Token::Ident(key)) | Some(Token::Str(key)) => key,
+                other => {
+                    return Err(expression_error(format!(
+                        "Expected a key in {}(..), found {:?}",
+                        name, other
+                    )))
+                }
+            };
+            self.expect(Token::RParen)?;
+            return match name {
+                "var" => self.resolver.get_variable(&key),
+                "output" => self.resolver.get_output(&key),
+                _ => self.resolver.get_data(&key),
+            };
+        }
+
+        let mut args = Vec::new();
+        if self.peek() == Some(&Token::RParen) {
+            self.pos += 1;
+        } else {
+            loop {
+                args.push(self.expr()?);
+                match self.next() {
+                    Some(Token::Comma) => continue,
+                    Some(Token::RParen) => break,
+                    other => {
+                        return Err(expression_error(format!(
+                            "Expected ',' or ')' in {}(..), found {:?}",
+                            name, other
+                        )))
+                    }
+                }
+            }
+        }
+
+        match (name, args.as_slice()) {
+            ("keccak256", [value]) => {
+                let bytes = bytes_of(value, true).ok_or_else(|| {
+                    expression_error(format!("Cannot hash {}", type_name(value)))
+                })?;
+                Ok(DynSolValue::FixedBytes(keccak256(bytes), 32))
+            }
+            ("abi.encode", _) => Ok(DynSolValue::Bytes(
+                DynSolValue::Tuple(args).abi_encode_params(),
+            )),
+            ("abi.encodePacked", _) => Ok(DynSolValue::Bytes(
+                DynSolValue::Tuple(args).abi_encode_packed(),
+            )),
+            ("concat", [first, ..]) => {
+                let first = first.clone();
+                args[1..].iter().cloned().try_fold(first, add)
+            }
+            ("address", [value]) => convert_value(value.clone(), &DynSolType::Address),
+            ("bytes32", [value]) => convert_value(value.clone(), &DynSolType::FixedBytes(32)),
+            ("uint256", [value]) => convert_value(value.clone(), &DynSolType::Uint(256)),
+            ("bytes", [value]) => convert_value(value.clone(), &DynSolType::Bytes),
+            ("string", [DynSolValue::Bytes(bytes)]) => String::from_utf8(bytes.clone())
+                .map(DynSolValue::String)
+                .map_err(|_e| expression_error("Bytes are not valid UTF-8")),
+            ("string", [value]) => convert_value(value.clone(), &DynSolType::String),
+            _ => Err(expression_error(format!(
+                "Unknown function {} with {} arguments",
+                name,
+                args.len()
+            ))),
+        }
+    }
+}
+
+/// Add uints, or concatenate two strings or two byte values
+fn add(lhs: DynSolValue, rhs: DynSolValue) -> Result<DynSolValue> {
+    match (&lhs, &rhs) {
+        (DynSolValue::Uint(a, _), DynSolValue::Uint(b, _)) => a
+            .checked_add(*b)
+            .map(|v| DynSolValue::Uint(v, 256))
+            .ok_or_else(|| expression_error("Arithmetic overflow")),
+        (DynSolValue::String(a), DynSolValue::String(b)) => {
+            Ok(DynSolValue::String(format!("{}{}", a, b)))
+        }
+        _ => match (bytes_of(&lhs, false), bytes_of(&rhs, false)) {
+            (Some(mut a), Some(b)) => {
+                a.extend(b);
+                Ok(DynSolValue::Bytes(a))
+            }
+            _ => Err(expression_error(format!(
+                "Cannot add {} and {}",
+                type_name(&lhs),
+                type_name(&rhs)
+            ))),
+        },
+    }
+}
+
+fn bytes_of(value: &DynSolValue, allow_string: bool) -> Option<Vec<u8>> {
+    match value {
+        DynSolValue::Bytes(bytes) => Some(bytes.clone()),
+        DynSolValue::FixedBytes(word, size) => Some(word[..*size].to_vec()),
+        DynSolValue::Address(address) => Some(address.to_vec()),
+        DynSolValue::String(s) if allow_string => Some(s.as_bytes().to_vec()),
+        _ => None,
+    }
+}
+
+fn uint(value: &DynSolValue) -> Result<U256> {
+    match value {
+        DynSolValue::Uint(v, _) => Ok(*v),
+        other => Err(expression_error(format!(
+            "Expected a uint, found {}",
+            type_name(other)
+        ))),
+    }
+}
+
+fn unit_multiplier(unit: &str) -> Option<U256> {
+    let multiplier: u64 = match unit {
+        "wei" | "seconds" => 1,
+        "gwei" => 1_000_000_000,
+        "ether" => 1_000_000_000_000_000_000,
+        "minutes" => 60,
+        "hours" => 3_600,
+        "days" => 86_400,
+        "weeks" => 604_800,
+        _ => return None,
+    };
+    Some(U256::from(multiplier))
+}
+
+/// Parse a decimal literal; fractions are only allowed when the unit makes
+/// the result a whole number, e.g. `1.5 ether`
+fn parse_number(number: &str, unit: Option<U256>) -> Result<U256> {
+    let invalid = || expression_error(format!("Invalid number {}", number));
+    let multiplier = unit.unwrap_or(U256::from(1));
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+
+    let whole = U256::from_str_radix(whole, 10).map_err(|_e| invalid())?;
+    let mut value = whole
+        .checked_mul(multiplier)
+        .ok_or_else(|| expression_error("Arithmetic overflow"))?;
+
+    if !fraction.is_empty() {
+        let numerator = U256::from_str_radix(fraction, 10)
+            .map_err(|_e| invalid())?
+            .checked_mul(multiplier)
+            .ok_or_else(|| expression_error("Arithmetic overflow"))?;
+        let denominator = U256::from(10)
+            .checked_pow(U256::from(fraction.len()))
+            .ok_or_else(invalid)?;
+        if numerator % denominator != U256::ZERO {
+            return Err(expression_error(format!(
+                "{} is not a whole number in the smallest unit",
+                number
+            )));
+        }
+        value = value
+            .checked_add(numerator / denominator)
+            .ok_or_else(|| expression_error("Arithmetic overflow"))?;
+    }
+
+    Ok(value)
+}
+
+fn type_name(value: &DynSolValue) -> String {
+    value
+        .sol_type_name()
+        .map(|name| name.into_owned())
+        .unwrap_or_else(|| "value".to_string())
+}
+
+fn expression_error(message: impl Into<String>) -> DeployerError {
+    DeployerError::Expression(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{address, b256, hex};
+    use std::collections::HashMap;
+
+    const OWNER: Address = address!("1111111111111111111111111111111111111111");
+
+    struct Values(HashMap<&'static str, DynSolValue>);
+
+    impl Values {
+        fn new() -> Self {
+            Self(HashMap::from([
+                ("amount", DynSolValue::Uint(U256::from(5), 256)),
+                ("name", DynSolValue::String("light".to_string())),
+                ("factory.address", DynSolValue::Address(OWNER)),
+            ]))
+        }
+    }
+
+    impl VariableResolver for Values {
+        fn get_variable(&self, key: &str) -> Result<DynSolValue> {
+            self.0
+                .get(key)
+                .cloned()
+                .ok_or_else(|| DeployerError::VariableNotFound(key.to_string()))
+        }
+
+        fn get_output(&self, id: &str) -> Result<DynSolValue> {
+            self.0
+                .get(id)
+                .cloned()
+                .ok_or_else(|| DeployerError::OutputNotFound(id.to_string()))
+        }
+
+        fn get_data(&self, path: &str) -> Result<DynSolValue> {
+            Err(DeployerError::Config(format!("Data reference not found: {}", path)))
+        }
+    }
+
+    fn eval(expr: &str) -> Result<DynSolValue> {
+        evaluate(expr, &Values::new())
+    }
+
+    fn uint(expr: &str) -> U256 {
+        match eval(expr).unwrap() {
+            DynSolValue::Uint(value, 256) => value,
+            other => panic!("{} evaluated to {:?}", expr, other),
+        }
+    }
+
+    fn error(expr: &str) -> String {
+        eval(expr).unwrap_err().to_string()
+    }
+
+    fn ether(amount: u64) -> U256 {
+        U256::from(amount) * U256::from(10).pow(U256::from(18))
+    }
+
+    #[test]
+    fn tokenizes_numbers_units_strings_and_paths() {
+        assert_eq!(
+            tokenize("1_000.5 ether + factory.address").unwrap(),
+            [
+                Token::Number("1000.5".to_string()),
+                Token::Ident("ether".to_string()),
+                Token::Plus,
+                Token::Ident("factory.address".to_string()),
+            ]
+        );
+        assert_eq!(
+            tokenize(r#"0xAb ** 'it\'s' "a\tb""#).unwrap(),
+            [
+                Token::Hex("Ab".to_string()),
+                Token::StarStar,
+                Token::Str("it's".to_string()),
+                Token::Str("a\tb".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn follows_operator_precedence() {
+        assert_eq!(uint("1 + 2 * 3 ** 2"), U256::from(19));
+        assert_eq!(uint("(1 + 2) * 3"), U256::from(9));
+        assert_eq!(uint("2 ** 3 ** 2"), U256::from(512));
+        assert_eq!(uint("10 - 4 - 3"), U256::from(3));
+        assert_eq!(uint("20 / 3 % 4"), U256::from(2));
+    }
+
+    #[test]
+    fn resolves_variables_and_references() {
+        assert_eq!(uint("amount * 2"), U256::from(10));
+        assert_eq!(uint("var('amount') + var(amount)"), U256::from(10));
+        assert_eq!(eval("output(factory.address)").unwrap(), DynSolValue::Address(OWNER));
+        assert_eq!(error("missing + 1"), "Variable not found: missing");
+    }
+
+    #[test]
+    fn checks_arithmetic() {
+        let max = U256::MAX.to_string();
+        assert_eq!(uint(&format!("{} - 1 + 1", max)), U256::MAX);
+        assert_eq!(error(&format!("{} + 1", max)), "Expression error: Arithmetic overflow");
+        assert_eq!(error("2 ** 255 * 2"), "Expression error: Arithmetic overflow");
+        assert_eq!(error("2 ** 256"), "Expression error: Arithmetic overflow");
+        assert_eq!(error("1 - 2"), "Expression error: Arithmetic underflow");
+        assert_eq!(error("1 / 0"), "Expression error: Division by zero");
+        assert_eq!(error("1 % 0"), "Expression error: Division by zero");
+    }
+
+    #[test]
+    fn applies_unit_suffixes() {
+        assert_eq!(uint("1.5 ether"), ether(3) / U256::from(2));
+        assert_eq!(uint("2 ether + 1 gwei"), ether(2) + U256::from(1_000_000_000));
+        assert_eq!(uint("1 days + 2 hours"), U256::from(93_600));
+        assert_eq!(uint("7 wei"), U256::from(7));
+        assert_eq!(
+            error("0.5 wei"),
+            "Expression error: 0.5 is not a whole number in the smallest unit"
+        );
+        assert_eq!(error("1.2.3"), "Expression error: Invalid number 1.2.3");
+    }
+
+    #[test]
+    fn hashes_strings_and_bytes() {
+        assert_eq!(
+            eval("keccak256('')").unwrap(),
+            DynSolValue::FixedBytes(
+                b256!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
+                32
+            )
+        );
+        assert_eq!(eval("keccak256(name)").unwrap(), eval("keccak256(0x6c69676874)").unwrap());
+        assert_eq!(
+            eval("keccak256(amount)").unwrap_err().to_string(),
+            "Expression error: Cannot hash uint256"
+        );
+    }
+
+    #[test]
+    fn encodes_abi_values() {
+        let encoded = eval("abi.encode(amount, factory.address)").unwrap();
+        let expected = hex::decode(concat!(
+            "0000000000000000000000000000000000000000000000000000000000000005",
+            "0000000000000000000000001111111111111111111111111111111111111111",
+        ))
+        .unwrap();
+        assert_eq!(encoded, DynSolValue::Bytes(expected));
+
+        let packed = eval("abi.encodePacked(name, factory.address, 0x01)").unwrap();
+        let expected = hex::decode("6c69676874111111111111111111111111111111111111111101").unwrap();
+        assert_eq!(packed, DynSolValue::Bytes(expected));
+    }
+
+    #[test]
+    fn concatenates_and_converts() {
+        assert_eq!(eval("name + '-v2'").unwrap(), DynSolValue::String("light-v2".to_string()));
+        assert_eq!(eval("0x01 + 0x0203").unwrap(), DynSolValue::Bytes(vec![1, 2, 3]));
+        assert_eq!(
+            evaluate_as("amount + 1", &DynSolType::Uint(8), &Values::new()).unwrap(),
+            DynSolValue::Uint(U256::from(6), 8)
+        );
+        assert!(matches!(
+            evaluate_as("256", &DynSolType::Uint(8), &Values::new()),
+            Err(DeployerError::TypeConversion { .. })
+        ));
+        assert_eq!(
+            eval("address(0x1111111111111111111111111111111111111111)").unwrap(),
+            DynSolValue::Address(OWNER)
+        );
+        assert_eq!(error("name + 1"), "Expression error: Cannot add string and uint256");
+    }
+
+    #[test]
+    fn limits_nesting_and_length() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(uint(&nested(MAX_DEPTH - 1)), U256::from(1));
+        assert_eq!(error(&nested(MAX_DEPTH)), "Expression error: Expression nested too deeply");
+        assert_eq!(
+            error(&"1+".repeat(MAX_LENGTH / 2 + 1)),
+            "Expression error: Expression exceeds 4096 characters"
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        assert_eq!(error("1 +"), "Expression error: Unexpected end of expression");
+        assert_eq!(error("1 # 2"), "Expression error: Unexpected character '#'");
+        assert_eq!(error("'open"), "Expression error: Unterminated string literal");
+        assert_eq!(error("(1 + 2"), "Expression error: Expected RParen, found None");
+        assert_eq!(error("1 2"), "Expression error: Unexpected Number(\"2\") in 1 2");
+        assert_eq!(error("foo(1, 2)"), "Expression error: Unknown function foo with 2 arguments");
+        assert_eq!(
+            error("keccak256(1 2)"),
+            "Expression error: Expected ',' or ')' in keccak256(..), found Some(Number(\"2\"))"
+        );
+    }
+
+    #[test]
+    fn references_identifiers_but_not_string_literals() {
+        assert_eq!(
+            referenced_names("amount * 2 + keccak256('api_key') + var('salt')"),
+            ["amount", "keccak256", "var", "salt"]
+        );
+        assert!(referenced_names("'unterminated").is_empty());
+    }
+}
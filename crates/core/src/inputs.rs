@@ -0,0 +1,235 @@
+use alloy::{
+    dyn_abi::DynSolValue,
+    primitives::{I256, U256},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    errors::{DeployerError, Result},
+    variables::{Literal, Variable, VariableResolver},
+};
+
+/// A run-time parameter declared in a pipeline's `inputs:` section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputDefinition {
+    /// Solidity type, as for `Variable::ty`
+    #[serde(alias = "type")]
+    pub ty: String,
+    /// Used when no value is supplied; inputs without a default are required
+    #[serde(default, alias = "default_value")]
+    pub default: Option<Literal>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub constraints: InputConstraints,
+}
+
+/// Validation applied to a supplied or default input value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InputConstraints {
+    /// Inclusive lower bound for integer types
+    #[serde(default)]
+    pub min: Option<String>,
+    /// Inclusive upper bound for integer types
+    #[serde(default)]
+    pub max: Option<String>,
+    /// Minimum length of strings, bytes and arrays
+    #[serde(default)]
+    pub min_length: Option<usize>,
+    /// Maximum length of strings, bytes and arrays
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    /// The value must equal one of these
+    #[serde(default)]
+    pub allowed: Vec<Literal>,
+    /// Reject the zero address, zero integers and all-zero bytes
+    #[serde(default)]
+    pub non_zero: bool,
+}
+
+/// Resolve the declared inputs against the supplied values, returning them
+/// as variables.
+///
+/// All missing and invalid inputs are reported together so a run can be
+/// rejected before it touches the chain.
+pub fn resolve_inputs(
+    definitions: &HashMap<String, InputDefinition>,
+    provided: &HashMap<String, Literal>,
+) -> Result<HashMap<String, Variable>> {
+    let mut unknown: Vec<&str> = provided
+        .keys()
+        .filter(|name| !definitions.contains_key(*name))
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        unknown.sort();
+        return Err(DeployerError::Config(format!(
+            "Unknown inputs: {}",
+            unknown.join(", ")
+        )));
+    }
+
+    // Sorted so errors are reported in a stable order
+    let definitions: BTreeMap<_, _> = definitions.iter().collect();
+    let mut missing = Vec::new();
+    let mut invalid = Vec::new();
+    let mut variables = HashMap::new();
+
+    for (name, definition) in definitions {
+        let value = match provided.get(name).or(definition.default.as_ref()) {
+            Some(value) => value.clone(),
+            None => {
+                missing.push(match &definition.description {
+                    Some(description) => format!("{} ({}: {})", name, definition.ty, description),
+                    None => format!("{} ({})", name, definition.ty),
+                });
+                continue;
+            }
+        };
+
+        let variable = Variable {
+            ty: definition.ty.clone(),
+            value,
+        };
+        match definition.validate(&variable) {
+            Ok(()) => {
+                variables.insert(name.clone(), variable);
+            }
+            Err(DeployerError::Config(message)) => invalid.push(format!("{}: {}", name, message)),
+            Err(e) => invalid.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    let mut errors = Vec::new();
+    if !missing.is_empty() {
+        errors.push(format!("Missing required inputs: {}", missing.join(", ")));
+    }
+    if !invalid.is_empty() {
+        errors.push(format!("Invalid inputs: {}", invalid.join("; ")));
+    }
+    if !errors.is_empty() {
+        return Err(DeployerError::Config(errors.join(". ")));
+    }
+
+    Ok(variables)
+}
+
+impl InputDefinition {
+    fn validate(&self, variable: &Variable) -> Result<()> {
+        // Secrets are only readable at execution time
+        if matches!(variable.value, Literal::Secret(_)) {
+            return Ok(());
+        }
+
+        let value = variable.resolve(&NoReferences)?;
+        let constraints = &self.constraints;
+
+        if let Some(min) = &constraints.min {
+            if compare_int(&value, min)?.is_lt() {
+                return Err(constraint_error(format!("must be at least {}", min)));
+            }
+        }
+        if let Some(max) = &constraints.max {
+            if compare_int(&value, max)?.is_gt() {
+                return Err(constraint_error(format!("must be at most {}", max)));
+            }
+        }
+
+        if constraints.min_length.is_some() || constraints.max_length.is_some() {
+            let length = length_of(&value).ok_or_else(|| {
+                constraint_error("length constraints only apply to strings, bytes and arrays")
+            })?;
+            if constraints.min_length.is_some_and(|min| length < min) {
+                return Err(constraint_error(format!(
+                    "length {} is below the minimum of {}",
+                    length,
+                    constraints.min_length.unwrap_or_default()
+                )));
+            }
+            if constraints.max_length.is_some_and(|max| length > max) {
+                return Err(constraint_error(format!(
+                    "length {} exceeds the maximum of {}",
+                    length,
+                    constraints.max_length.unwrap_or_default()
+                )));
+            }
+        }
+
+        if !constraints.allowed.is_empty() {
+            let allowed = constraints
+                .allowed
+                .iter()
+                .map(|literal| {
+                    Variable {
+                        ty: self.ty.clone(),
+                        value: literal.clone(),
+                    }
+                    .resolve(&NoReferences)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if !allowed.contains(&value) {
+                return Err(constraint_error("must be one of the allowed values"));
+            }
+        }
+
+        if constraints.non_zero && is_zero(&value) {
+            return Err(constraint_error("must be non-zero"));
+        }
+
+        Ok(())
+    }
+}
+
+fn compare_int(value: &DynSolValue, bound: &str) -> Result<std::cmp::Ordering> {
+    let invalid_bound = || constraint_error(format!("invalid bound {}", bound));
+    match value {
+        DynSolValue::Uint(v, _) => Ok(v.cmp(&bound.parse::<U256>().map_err(|_e| invalid_bound())?)),
+        DynSolValue::Int(v, _) => Ok(v.cmp(&bound.parse::<I256>().map_err(|_e| invalid_bound())?)),
+        _ => Err(constraint_error("min and max only apply to integer types")),
+    }
+}
+
+fn length_of(value: &DynSolValue) -> Option<usize> {
+    match value {
+        DynSolValue::String(s) => Some(s.len()),
+        DynSolValue::Bytes(b) => Some(b.len()),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) => Some(items.len()),
+        _ => None,
+    }
+}
+
+fn is_zero(value: &DynSolValue) -> bool {
+    match value {
+        DynSolValue::Address(a) => a.is_zero(),
+        DynSolValue::Uint(v, _) => v.is_zero(),
+        DynSolValue::Int(v, _) => v.is_zero(),
+        DynSolValue::FixedBytes(b, _) => b.is_zero(),
+        DynSolValue::Bytes(b) => b.iter().all(|byte| *byte == 0),
+        _ => false,
+    }
+}
+
+fn constraint_error(message: impl Into<String>) -> DeployerError {
+    DeployerError::Config(message.into())
+}
+
+/// Input values are literals, so nothing needs resolving
+struct NoReferences;
+
+impl VariableResolver for NoReferences {
+    fn get_variable(&self, key: &str) -> Result<DynSolValue> {
+        Err(DeployerError::VariableNotFound(key.to_string()))
+    }
+
+    fn get_output(&self, id: &str) -> Result<DynSolValue> {
+        Err(DeployerError::OutputNotFound(id.to_string()))
+    }
+
+    fn get_data(&self, path: &str) -> Result<DynSolValue> {
+        Err(DeployerError::Config(format!(
+            "Data references are not available for inputs: {}",
+            path
+        )))
+    }
+}
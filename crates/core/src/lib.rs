@@ -1,11 +1,29 @@
 pub mod actions;
+pub mod artifacts;
+pub mod compiler;
 pub mod data;
 pub mod errors;
+pub mod expression;
+pub mod inputs;
+pub mod linking;
+pub mod safe;
+pub mod secrets;
+pub mod storage_layout;
 pub mod types;
 pub mod variables;
+pub mod verification;
 
 pub use actions::*;
+pub use artifacts::*;
+pub use compiler::*;
 pub use data::*;
 pub use errors::*;
+pub use expression::*;
+pub use inputs::*;
+pub use linking::*;
+pub use safe::*;
+pub use secrets::*;
+pub use storage_layout::*;
 pub use types::*;
-pub use variables::*;
\ No newline at end of file
+pub use variables::*;
+pub use verification::*;
\ No newline at end of file
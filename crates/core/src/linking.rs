@@ -0,0 +1,79 @@
+use alloy::primitives::{keccak256, Address};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::errors::{DeployerError, Result};
+
+/// Byte range in the bytecode where a library address must be inserted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkReference {
+    pub start: usize,
+    pub length: usize,
+}
+
+/// Link references keyed by source file and then library name, as emitted by
+/// solc, Foundry and Hardhat
+pub type LinkReferences = HashMap<String, HashMap<String, Vec<LinkReference>>>;
+
+/// Replace unresolved library placeholders in hex bytecode with deployed
+/// library addresses.
+///
+/// Libraries may be keyed either by name (`MyLib`) or fully qualified name
+/// (`src/MyLib.sol:MyLib`). Positions from `link_references` are linked first;
+/// any remaining `__$<hash>$__` placeholders are then matched against the
+/// fully qualified names in `libraries`.
+pub fn link_bytecode(
+    bytecode: &str,
+    link_references: &LinkReferences,
+    libraries: &HashMap<String, Address>,
+) -> Result<String> {
+    let mut code = bytecode.trim_start_matches("0x").to_string();
+
+    for (file, libs) in link_references {
+        for (name, references) in libs {
+            let qualified_name = format!("{}:{}", file, name);
+            let address = libraries
+                .get(&qualified_name)
+                .or_else(|| libraries.get(name))
+                .ok_or_else(|| {
+                    DeployerError::Config(format!(
+                        "Bytecode requires library {}; add it to `libraries`",
+                        qualified_name
+                    ))
+                })?;
+            let address_hex = hex::encode(address);
+
+            for reference in references {
+                let start = reference.start * 2;
+                let end = start + reference.length * 2;
+                if reference.length != 20 || end > code.len() {
+                    return Err(DeployerError::Config(format!(
+                        "Invalid link reference for {} at byte {}",
+                        qualified_name, reference.start
+                    )));
+                }
+                code.replace_range(start..end, &address_hex);
+            }
+        }
+    }
+
+    for (name, address) in libraries.iter().filter(|(name, _)| name.contains(':')) {
+        code = code.replace(&library_placeholder(name), &hex::encode(address));
+    }
+
+    if let Some(pos) = code.find("__$") {
+        let placeholder = code.get(pos..pos + 40).unwrap_or(&code[pos..]);
+        return Err(DeployerError::Config(format!(
+            "Bytecode contains unlinked library placeholder {}",
+            placeholder
+        )));
+    }
+
+    Ok(format!("0x{}", code))
+}
+
+/// The `__$<hash>$__` placeholder solc emits for a fully qualified library name
+pub fn library_placeholder(qualified_name: &str) -> String {
+    let hash = hex::encode(keccak256(qualified_name.as_bytes()));
+    format!("__${}$__", &hash[..34])
+}
@@ -0,0 +1,77 @@
+use alloy::primitives::{Address, B256};
+use serde::{Deserialize, Serialize};
+
+use crate::variables::VariableValue;
+
+/// Account a write action is sent from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sender {
+    /// The pipeline's own key
+    #[default]
+    Eoa,
+    /// The pipeline's Safe, as configured in `safe:`
+    Safe,
+}
+
+/// What happens to the Safe transaction built from `sender: safe` writes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafeMode {
+    /// Write a Safe Transaction Builder JSON file to `output`
+    #[default]
+    TxBuilder,
+    /// Propose to a Safe transaction service at `service_url`, signed by the pipeline's key
+    Propose,
+    /// Sign with every key in `owners` and call `execTransaction` from the pipeline's key
+    Execute,
+}
+
+/// Safe multisig settings for a pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafeConfig {
+    pub address: VariableValue,
+    /// Sender of write actions that don't set their own
+    #[serde(default)]
+    pub sender: Sender,
+    #[serde(default)]
+    pub mode: SafeMode,
+    /// Transaction Builder file written in `tx_builder` mode
+    #[serde(default = "default_output")]
+    pub output: String,
+    /// Base URL of the transaction service used in `propose` mode,
+    /// e.g. `https://safe-transaction-sepolia.safe.global`
+    #[serde(default)]
+    pub service_url: Option<String>,
+    /// `MultiSendCallOnly` contract used to batch several calls; defaults to
+    /// the canonical v1.3.0 deployment
+    #[serde(default)]
+    pub multisend: Option<VariableValue>,
+    /// Safe nonce to use; defaults to the Safe's current nonce
+    #[serde(default)]
+    pub nonce: Option<u64>,
+    /// Name of the batch in the Transaction Builder and the proposal's origin
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Owner private keys that sign in `execute` mode, usually `!secret` values
+    #[serde(default)]
+    pub owners: Vec<VariableValue>,
+}
+
+fn default_output() -> String {
+    "safe-transactions.json".to_string()
+}
+
+/// A Safe transaction built from a pipeline's `sender: safe` writes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafeTransactionResult {
+    pub safe: Address,
+    pub safe_tx_hash: B256,
+    pub nonce: u64,
+    /// Write actions included in the transaction, in execution order
+    pub actions: Vec<String>,
+    pub mode: SafeMode,
+    /// Transaction Builder file, transaction service URL or, when executed,
+    /// the transaction hash
+    pub destination: String,
+}
@@ -0,0 +1,91 @@
+use serde::{Serialize, Serializer};
+use serde_yaml::Value as YamlValue;
+use std::{collections::HashMap, fmt, path::Path};
+
+use crate::errors::{DeployerError, Result};
+
+/// Placeholder shown wherever a secret value would otherwise be printed
+pub const REDACTED: &str = "<redacted>";
+
+/// A secret value that never appears in `Debug`, `Display` or serialized output
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// The underlying value; only for passing to the code that consumes it
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+/// Named secrets loaded from a local YAML or JSON file of `name: value` pairs
+#[derive(Debug, Clone, Default)]
+pub struct SecretStore {
+    secrets: HashMap<String, Secret>,
+}
+
+impl SecretStore {
+    /// Load secrets from `path`, or an empty store if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            DeployerError::Config(format!("Failed to read secrets file {}: {}", path.display(), e))
+        })?;
+        // Avoid echoing file contents in parse errors
+        let entries: HashMap<String, YamlValue> = serde_yaml::from_str(&content).map_err(|_e| {
+            DeployerError::Config(format!("Invalid secrets file {}", path.display()))
+        })?;
+
+        let secrets = entries
+            .into_iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    YamlValue::String(s) => s,
+                    YamlValue::Number(n) => n.to_string(),
+                    YamlValue::Bool(b) => b.to_string(),
+                    _ => {
+                        return Err(DeployerError::Config(format!(
+                            "Secret {} must be a scalar value",
+                            name
+                        )))
+                    }
+                };
+                Ok((name, Secret::new(value)))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        Ok(Self { secrets })
+    }
+
+    pub fn get(&self, name: &str) -> Result<Secret> {
+        self.secrets
+            .get(name)
+            .cloned()
+            .ok_or_else(|| DeployerError::Config(format!("Secret not found: {}", name)))
+    }
+}
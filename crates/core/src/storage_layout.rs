@@ -0,0 +1,789 @@
+use alloy::{
+    dyn_abi::{DynSolType, DynSolValue},
+    primitives::{keccak256, B256, U256},
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    data::ContractData,
+    errors::{DeployerError, Result},
+};
+
+/// Storage layout emitted by solc's `storageLayout` output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageLayout {
+    #[serde(default)]
+    pub storage: Vec<StorageItem>,
+    /// Type definitions keyed by type id, e.g. `t_uint256`; solc emits `null` when empty
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub types: HashMap<String, StorageType>,
+    /// ERC-7201 namespaces keyed by id (without the `erc7201:` prefix), with
+    /// slots relative to the namespace root. Empty when the namespace is
+    /// declared but its struct layout isn't part of the artifact
+    #[serde(default)]
+    pub namespaces: BTreeMap<String, Vec<StorageItem>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageItem {
+    pub label: String,
+    /// Decimal slot number
+    pub slot: String,
+    /// Byte offset within the slot
+    pub offset: u64,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageType {
+    /// `inplace`, `mapping`, `dynamic_array` or `bytes`
+    pub encoding: String,
+    pub label: String,
+    pub number_of_bytes: String,
+    #[serde(default)]
+    pub members: Option<Vec<StorageItem>>,
+    #[serde(default)]
+    pub base: Option<String>,
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+impl StorageLayout {
+    /// The layout recorded in a contract's artifact. Namespaces are taken from an
+    /// explicit `namespaces` entry and from structs annotated with
+    /// `@custom:storage-location erc7201:<id>` in the artifact's AST
+    pub fn from_contract(contract: &ContractData) -> Result<Self> {
+        let layout = contract.artifact.get("storageLayout").ok_or_else(|| {
+            DeployerError::Config(format!(
+                "Artifact of {} has no storageLayout; add storageLayout to the compiler's output selection",
+                contract.name
+            ))
+        })?;
+        let mut layout: Self = serde_json::from_value(layout.clone()).map_err(|e| {
+            DeployerError::Config(format!(
+                "Invalid storageLayout for {}: {}",
+                contract.name, e
+            ))
+        })?;
+
+        if let Some(ast) = contract.artifact.get("ast") {
+            let mut structs = Vec::new();
+            namespaced_structs(ast, &mut structs);
+            for (id, name) in structs {
+                let label = format!("struct {}", name);
+                let members = layout
+                    .types
+                    .values()
+                    .find(|ty| ty.label == label)
+                    .and_then(|ty| ty.members.clone())
+                    .unwrap_or_default();
+                layout.namespaces.entry(id).or_insert(members);
+            }
+        }
+
+        Ok(layout)
+    }
+}
+
+/// Root slot of an ERC-7201 namespace:
+/// `keccak256(abi.encode(uint256(keccak256(id)) - 1)) & ~bytes32(uint256(0xff))`
+pub fn erc7201_slot(id: &str) -> B256 {
+    let inner = U256::from_be_bytes(keccak256(id.as_bytes()).0) - U256::from(1);
+    let mut slot = keccak256(inner.to_be_bytes::<32>());
+    slot.0[31] = 0;
+    slot
+}
+
+/// Slot of a mapping entry, `keccak256(key . slot)`. Value-type keys are
+/// padded to a word; `string` and `bytes` keys are hashed unpadded
+pub fn mapping_slot(slot: B256, key: &DynSolValue) -> B256 {
+    let mut preimage = match key {
+        DynSolValue::String(s) => s.as_bytes().to_vec(),
+        DynSolValue::Bytes(b) => b.clone(),
+        other => other.abi_encode(),
+    };
+    preimage.extend_from_slice(slot.as_slice());
+    keccak256(preimage)
+}
+
+/// Decode a value type packed into a storage word. `offset` is counted in
+/// bytes from the low-order end of the word, as in solc's storage layout
+pub fn decode_storage_value(word: B256, ty: &DynSolType, offset: usize) -> Result<DynSolValue> {
+    let size = match ty {
+        DynSolType::Bool => 1,
+        DynSolType::Address => 20,
+        DynSolType::Int(bits) | DynSolType::Uint(bits) => bits / 8,
+        DynSolType::FixedBytes(size) => *size,
+        other => {
+            return Err(DeployerError::Config(format!(
+                "Cannot read {} from a single storage slot",
+                other.sol_type_name()
+            )))
+        }
+    };
+    if offset + size > 32 {
+        return Err(DeployerError::Config(format!(
+            "{} at offset {} does not fit in a storage slot",
+            ty.sol_type_name(),
+            offset
+        )));
+    }
+
+    // Re-pad the packed bytes into an ABI word and decode that
+    let value = &word[32 - offset - size..32 - offset];
+    let mut padded = [0u8; 32];
+    match ty {
+        DynSolType::FixedBytes(_) => padded[..size].copy_from_slice(value),
+        DynSolType::Int(_) if value[0] & 0x80 != 0 => {
+            padded.fill(0xff);
+            padded[32 - size..].copy_from_slice(value);
+        }
+        _ => padded[32 - size..].copy_from_slice(value),
+    }
+    ty.abi_decode(&padded)
+        .map_err(|e| DeployerError::Config(format!("Failed to decode storage value: {}", e)))
+}
+
+/// A single incompatibility between two storage layouts
+#[derive(Debug, Clone, Serialize)]
+pub struct LayoutIssue {
+    /// Where the problem is, e.g. `slot 3 offset 0` or `erc7201:app.main slot 1`
+    pub location: String,
+    pub message: String,
+}
+
+/// Result of comparing the storage layout of an implementation with its replacement
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LayoutReport {
+    pub issues: Vec<LayoutIssue>,
+}
+
+impl LayoutReport {
+    pub fn is_compatible(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for LayoutReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "  - {}: {}", issue.location, issue.message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check that `new` can replace `old` behind a proxy without corrupting storage.
+///
+/// Variables must keep their position, name and type; new variables may only be
+/// appended or take space from a `__gap` array whose end stays in place. Structs
+/// may only grow where they are mapping values, and namespaces may not be removed.
+pub fn check_storage_upgrade(old: &StorageLayout, new: &StorageLayout) -> LayoutReport {
+    let mut checker = LayoutChecker {
+        old: &old.types,
+        new: &new.types,
+        issues: Vec::new(),
+        visited: HashSet::new(),
+    };
+
+    checker.compare_items("", &old.storage, &new.storage, true);
+
+    for (id, old_items) in &old.namespaces {
+        let scope = format!("erc7201:{} ", id);
+        match new.namespaces.get(id) {
+            None => checker.issue(
+                format!("erc7201:{}", id),
+                "namespace was removed".to_string(),
+            ),
+            Some(new_items) if new_items.is_empty() && !old_items.is_empty() => checker.issue(
+                format!("erc7201:{}", id),
+                "layout of the namespace is missing from the new artifact".to_string(),
+            ),
+            Some(new_items) => checker.compare_items(&scope, old_items, new_items, true),
+        }
+    }
+
+    LayoutReport {
+        issues: checker.issues,
+    }
+}
+
+struct LayoutChecker<'a> {
+    old: &'a HashMap<String, StorageType>,
+    new: &'a HashMap<String, StorageType>,
+    issues: Vec<LayoutIssue>,
+    /// Type pairs being compared, so recursive structs terminate
+    visited: HashSet<(String, String)>,
+}
+
+impl LayoutChecker<'_> {
+    fn issue(&mut self, location: String, message: String) {
+        self.issues.push(LayoutIssue { location, message });
+    }
+
+    /// Compare variables or struct members. `appendable` is false for structs
+    /// stored inline, where any growth shifts whatever follows them
+    fn compare_items(
+        &mut self,
+        scope: &str,
+        old_items: &[StorageItem],
+        new_items: &[StorageItem],
+        appendable: bool,
+    ) {
+        let location =
+            |item: &StorageItem| format!("{}slot {} offset {}", scope, item.slot, item.offset);
+        let position = |item: &StorageItem| (slot_of(item), item.offset);
+
+        for old_item in old_items {
+            let same_label = new_items.iter().find(|n| n.label == old_item.label);
+
+            if is_gap(old_item, self.old) {
+                // A gap may shrink to make room for new variables as long as it still ends in the same place
+                if let Some(new_gap) = same_label.filter(|n| is_gap(n, self.new)) {
+                    let old_end = self.end_of(old_item, self.old);
+                    let new_end = self.end_of(new_gap, self.new);
+                    if old_end != new_end {
+                        self.issue(
+                            location(old_item),
+                            format!(
+                                "gap `{}` ended at slot {} but now ends at slot {}; resize it by the size of the added variables",
+                                old_item.label,
+                                slots(old_end),
+                                slots(new_end)
+                            ),
+                        );
+                    }
+                }
+                continue;
+            }
+
+            let at_position = new_items.iter().find(|n| position(n) == position(old_item));
+            match (at_position, same_label) {
+                (Some(new_item), _) if new_item.label == old_item.label => {
+                    self.compare_types(
+                        &location(old_item),
+                        &old_item.label,
+                        &old_item.ty,
+                        &new_item.ty,
+                        false,
+                    );
+                }
+                (_, Some(moved)) => self.issue(
+                    location(old_item),
+                    format!("`{}` moved to {}", old_item.label, location(moved)),
+                ),
+                (Some(new_item), None) => self.issue(
+                    location(old_item),
+                    format!(
+                        "`{}` was renamed or replaced by `{}`",
+                        old_item.label, new_item.label
+                    ),
+                ),
+                (None, None) => self.issue(
+                    location(old_item),
+                    format!("`{}` was removed", old_item.label),
+                ),
+            }
+        }
+
+        let occupied: Vec<_> = old_items
+            .iter()
+            .filter(|item| !is_gap(item, self.old))
+            .map(|item| {
+                (
+                    self.start_of(item),
+                    self.end_of(item, self.old),
+                    &item.label,
+                )
+            })
+            .collect();
+        let old_end = old_items
+            .iter()
+            .map(|item| self.end_of(item, self.old))
+            .max();
+
+        for new_item in new_items {
+            if old_items
+                .iter()
+                .any(|old_item| old_item.label == new_item.label)
+            {
+                continue;
+            }
+            let (start, end) = (self.start_of(new_item), self.end_of(new_item, self.new));
+            if let Some((_, _, label)) = occupied.iter().find(|(s, e, _)| start < *e && *s < end) {
+                self.issue(
+                    location(new_item),
+                    format!("new variable `{}` overlaps `{}`", new_item.label, label),
+                );
+            } else if !appendable && old_end.is_some_and(|old_end| end > old_end) {
+                self.issue(
+                    location(new_item),
+                    format!(
+                        "member `{}` was added to a struct stored inline, shifting the storage after it",
+                        new_item.label
+                    ),
+                );
+            }
+        }
+    }
+
+    fn compare_types(
+        &mut self,
+        location: &str,
+        path: &str,
+        old_id: &str,
+        new_id: &str,
+        appendable: bool,
+    ) {
+        // Recursive structs are only compared once per branch
+        let pair = (old_id.to_string(), new_id.to_string());
+        if !self.visited.insert(pair.clone()) {
+            return;
+        }
+        self.compare_type_definitions(location, path, old_id, new_id, appendable);
+        self.visited.remove(&pair);
+    }
+
+    fn compare_type_definitions(
+        &mut self,
+        location: &str,
+        path: &str,
+        old_id: &str,
+        new_id: &str,
+        appendable: bool,
+    ) {
+        let (old_types, new_types) = (self.old, self.new);
+        let (Some(old), Some(new)) = (old_types.get(old_id), new_types.get(new_id)) else {
+            if old_id != new_id {
+                self.issue(
+                    location.to_string(),
+                    format!("`{}` changed type from {} to {}", path, old_id, new_id),
+                );
+            }
+            return;
+        };
+
+        if old.encoding != new.encoding {
+            self.issue(
+                location.to_string(),
+                format!(
+                    "`{}` changed type from {} to {}",
+                    path, old.label, new.label
+                ),
+            );
+            return;
+        }
+
+        match old.encoding.as_str() {
+            "mapping" => {
+                if self.label_of(old.key.as_deref(), self.old)
+                    != self.label_of(new.key.as_deref(), self.new)
+                {
+                    self.issue(
+                        location.to_string(),
+                        format!(
+                            "`{}` changed mapping key from {} to {}",
+                            path, old.label, new.label
+                        ),
+                    );
+                }
+                if let (Some(old_value), Some(new_value)) = (&old.value, &new.value) {
+                    self.compare_types(
+                        location,
+                        &format!("{}[]", path),
+                        old_value,
+                        new_value,
+                        true,
+                    );
+                }
+            }
+            "dynamic_array" => {
+                if let (Some(old_base), Some(new_base)) = (&old.base, &new.base) {
+                    self.compare_types(location, &format!("{}[]", path), old_base, new_base, false);
+                }
+            }
+            _ => match (&old.members, &new.members) {
+                (Some(old_members), Some(new_members)) => {
+                    let scope = format!("{}, member at ", location);
+                    let before = self.issues.len();
+                    self.compare_items_in(&scope, path, old_members, new_members, appendable);
+                    if self.issues.len() == before
+                        && !appendable
+                        && old.number_of_bytes != new.number_of_bytes
+                    {
+                        self.size_changed(location, path, old, new);
+                    }
+                }
+                _ => {
+                    if let (Some(old_base), Some(new_base)) = (&old.base, &new.base) {
+                        self.compare_types(
+                            location,
+                            &format!("{}[]", path),
+                            old_base,
+                            new_base,
+                            false,
+                        );
+                    } else if normalize_label(&old.label) != normalize_label(&new.label) {
+                        self.issue(
+                            location.to_string(),
+                            format!(
+                                "`{}` changed type from {} to {}",
+                                path, old.label, new.label
+                            ),
+                        );
+                        return;
+                    }
+                    if old.number_of_bytes != new.number_of_bytes {
+                        self.size_changed(location, path, old, new);
+                    }
+                }
+            },
+        }
+    }
+
+    /// Struct members are compared with paths relative to the struct variable
+    fn compare_items_in(
+        &mut self,
+        scope: &str,
+        path: &str,
+        old_members: &[StorageItem],
+        new_members: &[StorageItem],
+        appendable: bool,
+    ) {
+        let before = self.issues.len();
+        self.compare_items(scope, old_members, new_members, appendable);
+        for issue in &mut self.issues[before..] {
+            issue.message = format!("in `{}`: {}", path, issue.message);
+        }
+    }
+
+    fn size_changed(&mut self, location: &str, path: &str, old: &StorageType, new: &StorageType) {
+        self.issue(
+            location.to_string(),
+            format!(
+                "`{}` changed size from {} to {} bytes",
+                path, old.number_of_bytes, new.number_of_bytes
+            ),
+        );
+    }
+
+    fn label_of<'t>(
+        &self,
+        id: Option<&'t str>,
+        types: &'t HashMap<String, StorageType>,
+    ) -> Option<&'t str> {
+        id.map(|id| types.get(id).map_or(id, |ty| ty.label.as_str()))
+    }
+
+    fn start_of(&self, item: &StorageItem) -> U256 {
+        slot_of(item) * U256::from(32) + U256::from(item.offset)
+    }
+
+    fn end_of(&self, item: &StorageItem, types: &HashMap<String, StorageType>) -> U256 {
+        let size = types
+            .get(&item.ty)
+            .and_then(|ty| ty.number_of_bytes.parse::<U256>().ok())
+            .unwrap_or(U256::from(32));
+        self.start_of(item) + size
+    }
+}
+
+fn is_gap(item: &StorageItem, types: &HashMap<String, StorageType>) -> bool {
+    item.label.starts_with("__gap")
+        && types
+            .get(&item.ty)
+            .is_some_and(|ty| ty.encoding == "inplace" && ty.base.is_some())
+}
+
+/// Number of slots spanned up to byte position `end`
+fn slots(end: U256) -> U256 {
+    (end + U256::from(31)) / U256::from(32)
+}
+
+fn slot_of(item: &StorageItem) -> U256 {
+    item.slot.parse().unwrap_or_default()
+}
+
+/// Contract and interface types are stored as addresses
+fn normalize_label(label: &str) -> &str {
+    if label.starts_with("contract ")
+        || label.starts_with("interface ")
+        || label == "address payable"
+    {
+        "address"
+    } else if label.starts_with("enum ") {
+        // Enums may gain values as long as their size doesn't change
+        "enum"
+    } else {
+        label
+    }
+}
+
+/// Collect `(namespace id, canonical struct name)` for structs annotated with
+/// `@custom:storage-location erc7201:<id>`
+fn namespaced_structs(node: &Value, out: &mut Vec<(String, String)>) {
+    match node {
+        Value::Object(map) => {
+            if map.get("nodeType").and_then(Value::as_str) == Some("StructDefinition") {
+                let documentation = match map.get("documentation") {
+                    Some(Value::String(text)) => Some(text.as_str()),
+                    Some(doc) => doc.get("text").and_then(Value::as_str),
+                    None => None,
+                };
+                let id = documentation.and_then(|doc| {
+                    doc.split("@custom:storage-location")
+                        .nth(1)?
+                        .split_whitespace()
+                        .next()?
+                        .strip_prefix("erc7201:")
+                        .map(String::from)
+                });
+                let name = map.get("canonicalName").and_then(Value::as_str);
+                if let (Some(id), Some(name)) = (id, name) {
+                    out.push((id, name.to_string()));
+                }
+            }
+            map.values()
+                .for_each(|value| namespaced_structs(value, out));
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|value| namespaced_structs(value, out)),
+        _ => {}
+    }
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn types() -> Value {
+        json!({
+            "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+            "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+            "t_enum(Kind)1": { "encoding": "inplace", "label": "enum Kind", "numberOfBytes": "1" },
+            "t_array(t_uint256)50_storage": {
+                "encoding": "inplace", "label": "uint256[50]", "numberOfBytes": "1600", "base": "t_uint256"
+            },
+            "t_array(t_uint256)49_storage": {
+                "encoding": "inplace", "label": "uint256[49]", "numberOfBytes": "1568", "base": "t_uint256"
+            },
+            "t_struct(Position)1_storage": {
+                "encoding": "inplace", "label": "struct Position", "numberOfBytes": "64",
+                "members": [
+                    { "label": "owner", "slot": "0", "offset": 0, "type": "t_address" },
+                    { "label": "amount", "slot": "1", "offset": 0, "type": "t_uint256" }
+                ]
+            },
+            "t_mapping(t_address,t_struct(Position)1_storage)": {
+                "encoding": "mapping", "label": "mapping(address => struct Position)", "numberOfBytes": "32",
+                "key": "t_address", "value": "t_struct(Position)1_storage"
+            }
+        })
+    }
+
+    /// `types()` with `Position` grown by a trailing `opened` member and `Kind` by a byte
+    fn grown_types() -> Value {
+        let mut types = types();
+        types["t_struct(Position)1_storage"]["numberOfBytes"] = json!("96");
+        types["t_struct(Position)1_storage"]["members"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({ "label": "opened", "slot": "2", "offset": 0, "type": "t_uint256" }));
+        types["t_enum(Kind)1"]["numberOfBytes"] = json!("2");
+        types
+    }
+
+    fn item(label: &str, slot: u64, ty: &str) -> Value {
+        json!({ "label": label, "slot": slot.to_string(), "offset": 0, "type": ty })
+    }
+
+    fn layout(storage: Vec<Value>, types: Value) -> StorageLayout {
+        serde_json::from_value(json!({ "storage": storage, "types": types })).unwrap()
+    }
+
+    fn messages(report: &LayoutReport) -> Vec<&str> {
+        report.issues.iter().map(|issue| issue.message.as_str()).collect()
+    }
+
+    #[test]
+    fn accepts_appended_variables() {
+        let old = layout(vec![item("owner", 0, "t_address")], types());
+        let new = layout(
+            vec![item("owner", 0, "t_address"), item("total", 1, "t_uint256")],
+            types(),
+        );
+
+        assert!(check_storage_upgrade(&old, &new).is_compatible());
+    }
+
+    #[test]
+    fn rejects_reordered_variables() {
+        let old = layout(
+            vec![item("owner", 0, "t_address"), item("total", 1, "t_uint256")],
+            types(),
+        );
+        let new = layout(
+            vec![item("total", 0, "t_uint256"), item("owner", 1, "t_address")],
+            types(),
+        );
+
+        let report = check_storage_upgrade(&old, &new);
+        assert_eq!(
+            messages(&report),
+            [
+                "`owner` moved to slot 1 offset 0",
+                "`total` moved to slot 0 offset 0"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_changed_types() {
+        let old = layout(vec![item("owner", 0, "t_address")], types());
+        let new = layout(vec![item("owner", 0, "t_uint256")], types());
+
+        let report = check_storage_upgrade(&old, &new);
+        assert_eq!(messages(&report), ["`owner` changed type from address to uint256"]);
+        assert_eq!(report.issues[0].location, "slot 0 offset 0");
+    }
+
+    #[test]
+    fn rejects_changed_sizes() {
+        let old = layout(vec![item("kind", 0, "t_enum(Kind)1")], types());
+        let new = layout(vec![item("kind", 0, "t_enum(Kind)1")], grown_types());
+
+        let report = check_storage_upgrade(&old, &new);
+        assert_eq!(messages(&report), ["`kind` changed size from 1 to 2 bytes"]);
+    }
+
+    #[test]
+    fn rejects_renamed_variables() {
+        let old = layout(vec![item("owner", 0, "t_address")], types());
+        let new = layout(vec![item("admin", 0, "t_address")], types());
+
+        let report = check_storage_upgrade(&old, &new);
+        assert_eq!(
+            messages(&report),
+            [
+                "`owner` was renamed or replaced by `admin`",
+                "new variable `admin` overlaps `owner`"
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_variables_taken_from_a_shrunk_gap() {
+        let old = layout(
+            vec![
+                item("owner", 0, "t_address"),
+                item("__gap", 1, "t_array(t_uint256)50_storage"),
+            ],
+            types(),
+        );
+        let new = layout(
+            vec![
+                item("owner", 0, "t_address"),
+                item("total", 1, "t_uint256"),
+                item("__gap", 2, "t_array(t_uint256)49_storage"),
+            ],
+            types(),
+        );
+
+        assert!(check_storage_upgrade(&old, &new).is_compatible());
+    }
+
+    #[test]
+    fn rejects_a_gap_that_wasnt_shrunk() {
+        let old = layout(
+            vec![
+                item("owner", 0, "t_address"),
+                item("__gap", 1, "t_array(t_uint256)50_storage"),
+            ],
+            types(),
+        );
+        let new = layout(
+            vec![
+                item("owner", 0, "t_address"),
+                item("total", 1, "t_uint256"),
+                item("__gap", 2, "t_array(t_uint256)50_storage"),
+            ],
+            types(),
+        );
+
+        let report = check_storage_upgrade(&old, &new);
+        assert_eq!(
+            messages(&report),
+            ["gap `__gap` ended at slot 51 but now ends at slot 52; resize it by the size of the added variables"]
+        );
+    }
+
+    #[test]
+    fn rejects_struct_growth_inline() {
+        let old = layout(vec![item("position", 0, "t_struct(Position)1_storage")], types());
+        let new = layout(
+            vec![item("position", 0, "t_struct(Position)1_storage")],
+            grown_types(),
+        );
+
+        let report = check_storage_upgrade(&old, &new);
+        assert_eq!(
+            messages(&report),
+            ["in `position`: member `opened` was added to a struct stored inline, shifting the storage after it"]
+        );
+    }
+
+    #[test]
+    fn accepts_struct_growth_in_a_mapping() {
+        let ty = "t_mapping(t_address,t_struct(Position)1_storage)";
+        let old = layout(vec![item("positions", 0, ty)], types());
+        let new = layout(vec![item("positions", 0, ty)], grown_types());
+
+        assert!(check_storage_upgrade(&old, &new).is_compatible());
+    }
+
+    #[test]
+    fn rejects_removed_namespaces() {
+        let mut old = layout(vec![], types());
+        old.namespaces.insert(
+            "app.main".to_string(),
+            vec![serde_json::from_value(item("owner", 0, "t_address")).unwrap()],
+        );
+        let new = layout(vec![], types());
+
+        let report = check_storage_upgrade(&old, &new);
+        assert_eq!(messages(&report), ["namespace was removed"]);
+        assert_eq!(report.issues[0].location, "erc7201:app.main");
+
+        // Kept namespaces are compared like top-level storage
+        let mut kept = layout(vec![], types());
+        kept.namespaces.insert(
+            "app.main".to_string(),
+            vec![serde_json::from_value(item("owner", 0, "t_uint256")).unwrap()],
+        );
+        let report = check_storage_upgrade(&old, &kept);
+        assert_eq!(messages(&report), ["`owner` changed type from address to uint256"]);
+        assert_eq!(report.issues[0].location, "erc7201:app.main slot 0 offset 0");
+    }
+}
@@ -22,4 +22,12 @@ pub struct ExecutionContext {
     pub gas_price: Option<U256>,
     pub gas_limit: Option<U256>,
     pub nonce: Option<u64>,
-}
\ No newline at end of file
+}
+
+/// Results collected while running a pipeline
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunOutput {
+    pub verifications: Vec<crate::VerificationResult>,
+    #[serde(default)]
+    pub safe_transactions: Vec<crate::SafeTransactionResult>,
+}
@@ -1,13 +1,195 @@
-use alloy::dyn_abi::{DynSolType, DynSolValue};
-use serde::{de::Error, Deserialize, Deserializer, Serialize};
+use alloy::{
+    dyn_abi::{DynSolType, DynSolValue, Specifier},
+    json_abi::{JsonAbi, Param},
+};
+use serde::{de::Error, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
 use serde_yaml::Value as YamlValue;
+use std::collections::BTreeMap;
 
-use crate::errors::{DeployerError, Result};
+use crate::{
+    data::ContractData,
+    errors::{DeployerError, Result},
+    expression,
+    secrets::{Secret, REDACTED},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Variable {
+    /// Solidity type; tuples may name their components, e.g.
+    /// `(address owner, uint256 amount)[]`, so maps can be matched by field
     pub ty: String,
-    pub value: String,
+    pub value: Literal,
+}
+
+impl Variable {
+    /// Coerce the literal value into the variable's type; `resolver` is only
+    /// consulted for `!secret` values
+    pub fn resolve<R: VariableResolver>(&self, resolver: &R) -> Result<DynSolValue> {
+        let param = parse_param(&self.ty).ok_or_else(|| DeployerError::TypeConversion {
+            expected: self.ty.clone(),
+            actual: "parse error".to_string(),
+        })?;
+        VariableValue::from(&self.value).resolve_param(&param, resolver)
+    }
+}
+
+/// An `!env` value: the environment variable `name`, falling back to
+/// `default`, optionally parsed as `ty` rather than the expected type
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvValue {
+    pub name: String,
+    pub default: Option<String>,
+    #[serde(rename = "type")]
+    pub ty: Option<String>,
+}
+
+impl EnvValue {
+    fn read(&self) -> Result<String> {
+        match std::env::var(&self.name) {
+            Ok(value) => Ok(value),
+            Err(_) => self.default.clone().ok_or_else(|| {
+                DeployerError::Config(format!("Environment variable {} is not set", self.name))
+            }),
+        }
+    }
+
+    fn resolve(&self, expected_type: &DynSolType) -> Result<DynSolValue> {
+        let raw = self.read()?;
+        let ty = match &self.ty {
+            Some(ty) => parse_param(ty)
+                .and_then(|param| param.resolve().ok())
+                .ok_or_else(|| DeployerError::TypeConversion {
+                    expected: ty.clone(),
+                    actual: "parse error".to_string(),
+                })?,
+            None => expected_type.clone(),
+        };
+        let value = ty.coerce_str(&raw).map_err(|_e| DeployerError::TypeConversion {
+            expected: ty.sol_type_name().into_owned(),
+            actual: format!("{}={}", self.name, raw),
+        })?;
+        expression::convert_value(value, expected_type)
+    }
+}
+
+/// Parse a type such as `(address owner, uint256 amount)[]` into an ABI
+/// parameter, keeping component names that the ABI type parser discards
+fn parse_param(ty: &str) -> Option<Param> {
+    let (param, rest) = parse_param_prefix(ty.trim())?;
+    rest.trim().is_empty().then_some(param)
+}
+
+fn parse_param_prefix(input: &str) -> Option<(Param, &str)> {
+    let input = input.trim_start();
+    let (mut ty, components, rest) = match input.strip_prefix("tuple(").or_else(|| input.strip_prefix('(')) {
+        Some(mut rest) => {
+            let mut components = Vec::new();
+            loop {
+                rest = rest.trim_start();
+                if let Some(after) = rest.strip_prefix(')') {
+                    break ("tuple".to_string(), components, after);
+                }
+                let (component, after) = parse_param_prefix(rest)?;
+                components.push(component);
+                rest = after.trim_start();
+                rest = rest.strip_prefix(',').unwrap_or(rest);
+            }
+        }
+        None => {
+            let end = input
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(input.len());
+            if end == 0 {
+                return None;
+            }
+            (input[..end].to_string(), Vec::new(), &input[end..])
+        }
+    };
+
+    // Array suffixes, e.g. `[]` or `[3]`
+    let mut rest = rest;
+    while let Some(after) = rest.strip_prefix('[') {
+        let end = after.find(']')?;
+        ty.push_str(&rest[..end + 2]);
+        rest = &after[end + 1..];
+    }
+
+    // Optional component name
+    let trimmed = rest.trim_start();
+    let end = trimmed
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+        .unwrap_or(trimmed.len());
+    let (name, rest) = if end > 0 && trimmed.len() < rest.len() {
+        (trimmed[..end].to_string(), &trimmed[end..])
+    } else {
+        (String::new(), rest)
+    };
+
+    let param = Param {
+        ty,
+        name,
+        components,
+        internal_type: None,
+    };
+    Some((param, rest))
+}
+
+/// A literal variable value: a scalar, a YAML sequence or map for array,
+/// tuple and struct types, or a value read from the environment or secrets
+#[derive(Debug, Clone)]
+pub enum Literal {
+    Scalar(String),
+    List(Vec<Literal>),
+    Map(BTreeMap<String, Literal>),
+    Env(EnvValue),
+    /// Name of an entry in the secrets file
+    Secret(String),
+}
+
+impl Literal {
+    /// Whether the value is or includes a `!secret`
+    pub fn contains_secret(&self) -> bool {
+        match self {
+            Literal::Secret(_) => true,
+            Literal::List(items) => items.iter().any(Literal::contains_secret),
+            Literal::Map(entries) => entries.values().any(Literal::contains_secret),
+            Literal::Scalar(_) | Literal::Env(_) => false,
+        }
+    }
+}
+
+impl Serialize for Literal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Literal::Scalar(s) => serializer.serialize_str(s),
+            Literal::List(items) => items.serialize(serializer),
+            Literal::Map(entries) => entries.serialize(serializer),
+            Literal::Env(env) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("env", env)?;
+                map.end()
+            }
+            Literal::Secret(name) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("secret", name)?;
+                map.end()
+            }
+        }
+    }
+}
+
+impl From<&Literal> for VariableValue {
+    fn from(literal: &Literal) -> Self {
+        match literal {
+            Literal::Scalar(s) => VariableValue::Value(s.clone()),
+            Literal::List(items) => VariableValue::List(items.iter().map(Into::into).collect()),
+            Literal::Map(entries) => VariableValue::Map(
+                entries.iter().map(|(k, v)| (k.clone(), v.into())).collect(),
+            ),
+            Literal::Env(env) => VariableValue::Env(env.clone()),
+            Literal::Secret(name) => VariableValue::Secret(name.clone()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -16,6 +198,15 @@ pub enum VariableValue {
     Output(String),
     Value(String),
     Data(String),
+    /// Expression evaluated by `expression::evaluate`, e.g. `stake_amount * 2`
+    Expr(String),
+    Env(EnvValue),
+    /// Name of an entry in the secrets file; the value itself is never stored
+    Secret(String),
+    /// Array or tuple elements, each of which may itself be a reference
+    List(Vec<VariableValue>),
+    /// Tuple or struct fields keyed by ABI component name
+    Map(BTreeMap<String, VariableValue>),
 }
 
 impl VariableValue {
@@ -24,18 +215,125 @@ impl VariableValue {
         expected_type: DynSolType,
         resolver: &R,
     ) -> Result<DynSolValue> {
+        self.resolve_typed(&expected_type, &[], resolver)
+    }
+
+    /// Resolve against an ABI parameter, whose component names are used to
+    /// match the fields of map values
+    pub fn resolve_param<R: VariableResolver>(
+        &self,
+        param: &Param,
+        resolver: &R,
+    ) -> Result<DynSolValue> {
+        let expected_type = param.resolve().map_err(|_e| DeployerError::TypeConversion {
+            expected: param.ty.clone(),
+            actual: "parse error".to_string(),
+        })?;
+        self.resolve_typed(&expected_type, &param.components, resolver)
+    }
+
+    /// Whether the value is a secret or is computed from one, in which case
+    /// it must not be shown in messages
+    pub fn references_secret<R: VariableResolver>(&self, resolver: &R) -> bool {
+        match self {
+            VariableValue::Secret(_) => true,
+            VariableValue::Var(key) => resolver.is_secret_variable(key),
+            VariableValue::Expr(expr) => crate::expression::referenced_names(expr)
+                .iter()
+                .any(|name| resolver.is_secret_variable(name)),
+            VariableValue::List(items) => items.iter().any(|item| item.references_secret(resolver)),
+            VariableValue::Map(fields) => {
+                fields.values().any(|field| field.references_secret(resolver))
+            }
+            _ => false,
+        }
+    }
+
+    fn resolve_typed<R: VariableResolver>(
+        &self,
+        expected_type: &DynSolType,
+        components: &[Param],
+        resolver: &R,
+    ) -> Result<DynSolValue> {
+        let mismatch = |actual: &str| DeployerError::TypeConversion {
+            expected: expected_type.sol_type_name().into_owned(),
+            actual: actual.to_string(),
+        };
+
         match self {
             VariableValue::Var(key) => resolver.get_variable(key),
             VariableValue::Output(id) => resolver.get_output(id),
-            VariableValue::Value(value) => {
+            VariableValue::Value(value) => expected_type
+                .coerce_str(value)
+                .map_err(|_e| mismatch(value)),
+            VariableValue::Data(path) => resolver.get_data(path),
+            VariableValue::Expr(expr) => expression::evaluate_as(expr, expected_type, resolver),
+            VariableValue::Env(env) => env.resolve(expected_type),
+            VariableValue::Secret(name) => {
+                let secret = resolver.get_secret(name)?;
                 expected_type
-                    .coerce_str(value)
-                    .map_err(|_e| DeployerError::TypeConversion {
-                        expected: format!("{:?}", expected_type),
-                        actual: value.clone(),
+                    .coerce_str(secret.expose())
+                    .map_err(|_e| mismatch(REDACTED))
+            }
+            VariableValue::List(items) => match expected_type {
+                // Array elements share the element type's components
+                DynSolType::Array(inner) => items
+                    .iter()
+                    .map(|item| item.resolve_typed(inner, components, resolver))
+                    .collect::<Result<Vec<_>>>()
+                    .map(DynSolValue::Array),
+                DynSolType::FixedArray(inner, len) => {
+                    if items.len() != *len {
+                        return Err(mismatch(&format!("list of {} elements", items.len())));
+                    }
+                    items
+                        .iter()
+                        .map(|item| item.resolve_typed(inner, components, resolver))
+                        .collect::<Result<Vec<_>>>()
+                        .map(DynSolValue::FixedArray)
+                }
+                _ => {
+                    let types = expected_type.as_tuple().ok_or_else(|| mismatch("list"))?;
+                    if items.len() != types.len() {
+                        return Err(mismatch(&format!("list of {} elements", items.len())));
+                    }
+                    items
+                        .iter()
+                        .zip(types)
+                        .enumerate()
+                        .map(|(i, (item, ty))| {
+                            let inner = components.get(i).map(|c| c.components.as_slice());
+                            item.resolve_typed(ty, inner.unwrap_or_default(), resolver)
+                        })
+                        .collect::<Result<Vec<_>>>()
+                        .map(DynSolValue::Tuple)
+                }
+            },
+            VariableValue::Map(entries) => {
+                let types = expected_type.as_tuple().ok_or_else(|| mismatch("map"))?;
+                if components.len() != types.len() || components.iter().any(|c| c.name.is_empty()) {
+                    return Err(DeployerError::Config(format!(
+                        "Cannot match fields of {} without ABI component names",
+                        expected_type.sol_type_name()
+                    )));
+                }
+
+                if let Some(unknown) = entries.keys().find(|k| !components.iter().any(|c| &c.name == *k)) {
+                    return Err(DeployerError::Config(format!("Unknown field: {}", unknown)));
+                }
+
+                components
+                    .iter()
+                    .zip(types)
+                    .map(|(component, ty)| {
+                        let entry = entries.get(&component.name).ok_or_else(|| {
+                            DeployerError::Config(format!("Missing field: {}", component.name))
+                        })?;
+                        entry.resolve_typed(ty, &component.components, resolver)
                     })
+                    .collect::<Result<Vec<_>>>()
+                    .map(DynSolValue::Tuple)
             }
-            VariableValue::Data(path) => resolver.get_data(path),
         }
     }
 }
@@ -44,6 +342,32 @@ pub trait VariableResolver {
     fn get_variable(&self, key: &str) -> Result<DynSolValue>;
     fn get_output(&self, id: &str) -> Result<DynSolValue>;
     fn get_data(&self, path: &str) -> Result<DynSolValue>;
+
+    /// Load the contract behind a contract data reference
+    fn get_contract(&self, key: &str) -> Result<ContractData> {
+        Err(DeployerError::Config(format!(
+            "Contract data not supported by this resolver: {}",
+            key
+        )))
+    }
+
+    /// Load the full ABI of a contract data reference
+    fn get_contract_abi(&self, key: &str) -> Result<JsonAbi> {
+        self.get_contract(key)?.json_abi()
+    }
+
+    /// Look up a named secret for a `!secret` value
+    fn get_secret(&self, name: &str) -> Result<Secret> {
+        Err(DeployerError::Config(format!(
+            "Secrets not supported by this resolver: {}",
+            name
+        )))
+    }
+
+    /// Whether a variable holds a `!secret`, so its value must not be shown
+    fn is_secret_variable(&self, _key: &str) -> bool {
+        false
+    }
 }
 
 impl<'de> Deserialize<'de> for VariableValue {
@@ -52,33 +376,143 @@ impl<'de> Deserialize<'de> for VariableValue {
         D: Deserializer<'de>,
     {
         let yaml_value = YamlValue::deserialize(deserializer)?;
-        match yaml_value {
-            YamlValue::Tagged(tagged) => match tagged.tag.to_string().as_str() {
-                "!var" => {
-                    if let YamlValue::String(s) = tagged.value {
-                        Ok(VariableValue::Var(s))
-                    } else {
-                        Err(Error::custom("Expected string value for !var"))
-                    }
+        variable_value_from_yaml(yaml_value).map_err(Error::custom)
+    }
+}
+
+fn variable_value_from_yaml(yaml_value: YamlValue) -> std::result::Result<VariableValue, String> {
+    match yaml_value {
+        YamlValue::Tagged(tagged) => match tagged.tag.to_string().as_str() {
+            "!var" => {
+                if let YamlValue::String(s) = tagged.value {
+                    Ok(VariableValue::Var(s))
+                } else {
+                    Err("Expected string value for !var".to_string())
                 }
-                "!output" => {
-                    if let YamlValue::String(s) = tagged.value {
-                        Ok(VariableValue::Output(s))
-                    } else {
-                        Err(Error::custom("Expected string value for !output"))
-                    }
+            }
+            "!output" => {
+                if let YamlValue::String(s) = tagged.value {
+                    Ok(VariableValue::Output(s))
+                } else {
+                    Err("Expected string value for !output".to_string())
                 }
-                "!data" => {
-                    if let YamlValue::String(s) = tagged.value {
-                        Ok(VariableValue::Data(s))
-                    } else {
-                        Err(Error::custom("Expected string value for !data"))
-                    }
+            }
+            "!data" => {
+                if let YamlValue::String(s) = tagged.value {
+                    Ok(VariableValue::Data(s))
+                } else {
+                    Err("Expected string value for !data".to_string())
                 }
-                _ => Err(Error::custom(format!("Unknown tag: {}", tagged.tag))),
-            },
-            YamlValue::String(s) => Ok(VariableValue::Value(s)),
-            _ => Err(Error::custom("Expected string or tagged value")),
+            }
+            "!expr" => {
+                if let YamlValue::String(s) = tagged.value {
+                    Ok(VariableValue::Expr(s))
+                } else {
+                    Err("Expected string value for !expr".to_string())
+                }
+            }
+            "!env" => env_from_yaml(tagged.value).map(VariableValue::Env),
+            "!secret" => {
+                if let YamlValue::String(s) = tagged.value {
+                    Ok(VariableValue::Secret(s))
+                } else {
+                    Err("Expected string value for !secret".to_string())
+                }
+            }
+            _ => Err(format!("Unknown tag: {}", tagged.tag)),
+        },
+        YamlValue::Sequence(items) => items
+            .into_iter()
+            .map(variable_value_from_yaml)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map(VariableValue::List),
+        YamlValue::Mapping(entries) => entries
+            .into_iter()
+            .map(|(k, v)| Ok((yaml_key(k)?, variable_value_from_yaml(v)?)))
+            .collect::<std::result::Result<BTreeMap<_, _>, String>>()
+            .map(VariableValue::Map),
+        other => yaml_scalar(other).map(VariableValue::Value),
+    }
+}
+
+impl<'de> Deserialize<'de> for Literal {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let yaml_value = YamlValue::deserialize(deserializer)?;
+        literal_from_yaml(yaml_value).map_err(Error::custom)
+    }
+}
+
+fn literal_from_yaml(yaml_value: YamlValue) -> std::result::Result<Literal, String> {
+    match yaml_value {
+        YamlValue::Tagged(tagged) => match tagged.tag.to_string().as_str() {
+            "!env" => env_from_yaml(tagged.value).map(Literal::Env),
+            "!secret" => {
+                if let YamlValue::String(s) = tagged.value {
+                    Ok(Literal::Secret(s))
+                } else {
+                    Err("Expected string value for !secret".to_string())
+                }
+            }
+            _ => Err(format!("Unexpected tag: {}", tagged.tag)),
+        },
+        YamlValue::Sequence(items) => items
+            .into_iter()
+            .map(literal_from_yaml)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map(Literal::List),
+        YamlValue::Mapping(entries) => entries
+            .into_iter()
+            .map(|(k, v)| Ok((yaml_key(k)?, literal_from_yaml(v)?)))
+            .collect::<std::result::Result<BTreeMap<_, _>, String>>()
+            .map(Literal::Map),
+        other => yaml_scalar(other).map(Literal::Scalar),
+    }
+}
+
+/// Scalars are kept as strings and coerced once the Solidity type is known
+fn yaml_scalar(yaml_value: YamlValue) -> std::result::Result<String, String> {
+    match yaml_value {
+        YamlValue::String(s) => Ok(s),
+        YamlValue::Number(n) => Ok(n.to_string()),
+        YamlValue::Bool(b) => Ok(b.to_string()),
+        YamlValue::Tagged(tagged) => Err(format!("Unexpected tag: {}", tagged.tag)),
+        _ => Err("Expected string, sequence, map or tagged value".to_string()),
+    }
+}
+
+/// `!env NAME` or `!env { name: NAME, default: .., type: .. }`
+fn env_from_yaml(yaml_value: YamlValue) -> std::result::Result<EnvValue, String> {
+    match yaml_value {
+        YamlValue::String(name) => Ok(EnvValue {
+            name,
+            default: None,
+            ty: None,
+        }),
+        YamlValue::Mapping(mut entries) => {
+            let mut field = |key: &str| {
+                entries
+                    .remove(key)
+                    .map(yaml_scalar)
+                    .transpose()
+            };
+            let name = field("name")?.ok_or("Missing name for !env")?;
+            let default = field("default")?;
+            let ty = field("type")?;
+            if let Some((key, _)) = entries.into_iter().next() {
+                return Err(format!("Unknown field for !env: {:?}", key));
+            }
+            Ok(EnvValue { name, default, ty })
         }
+        _ => Err("Expected a name or mapping for !env".to_string()),
+    }
+}
+
+fn yaml_key(key: YamlValue) -> std::result::Result<String, String> {
+    match key {
+        YamlValue::String(s) => Ok(s),
+        _ => Err("Map keys must be strings".to_string()),
     }
 }
@@ -0,0 +1,64 @@
+use alloy::primitives::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::VariableValue;
+
+/// Block explorer verification settings for a pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// API endpoint, e.g. `https://api.etherscan.io/v2/api` or `https://sourcify.dev/server`
+    pub url: String,
+    #[serde(default)]
+    pub provider: VerificationProvider,
+    /// Explorer API key, usually `!env` or `!secret` so it stays out of the config
+    #[serde(default)]
+    pub api_key: Option<VariableValue>,
+    /// Seconds to wait between status checks
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval_secs: u64,
+    /// Status checks before giving up and reporting the verification as pending
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+}
+
+fn default_poll_interval() -> u64 {
+    5
+}
+
+fn default_max_attempts() -> u32 {
+    12
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationProvider {
+    /// Etherscan-compatible `module=contract&action=verifysourcecode` API
+    #[default]
+    Etherscan,
+    /// Sourcify-compatible `/v2/verify` API
+    Sourcify,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStatus {
+    Verified,
+    AlreadyVerified,
+    /// Still queued after the configured number of status checks
+    Pending,
+    Failed,
+    /// Verification could not be attempted, e.g. no contract reference
+    Skipped,
+}
+
+/// Outcome of verifying one deployed contract
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationResult {
+    pub action_id: String,
+    pub address: Address,
+    pub provider: VerificationProvider,
+    pub status: VerificationStatus,
+    pub message: String,
+}
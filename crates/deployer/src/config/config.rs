@@ -1,14 +1,25 @@
-use deployer_core::{Action, Variable, DataReference, PipelineConfig};
+use deployer_core::{
+    resolve_inputs, Action, DataReference, InputDefinition, Literal, PipelineConfig, ReadBatchConfig, Variable,
+    SafeConfig, VariableData, VerificationConfig,
+};
 use serde::Deserialize;
-use std::{collections::HashMap, fs};
+use std::{collections::HashMap, fs, path::Path};
 
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub variables: HashMap<String, Variable>,
     #[serde(default)]
+    pub inputs: HashMap<String, InputDefinition>,
+    #[serde(default)]
     pub data: HashMap<String, DataReference>,
     pub actions: Vec<Action>,
+    #[serde(default)]
+    pub verification: Option<VerificationConfig>,
+    #[serde(default)]
+    pub safe: Option<SafeConfig>,
+    #[serde(default)]
+    pub read_batch: Option<ReadBatchConfig>,
 }
 
 impl Config {
@@ -19,8 +30,12 @@ impl Config {
         if let Ok(pipeline_config) = serde_yaml::from_str::<PipelineConfig>(&contents) {
             return Ok(Config {
                 variables: pipeline_config.variables,
+                inputs: pipeline_config.inputs,
                 data: pipeline_config.data,
                 actions: pipeline_config.actions,
+                verification: pipeline_config.verification,
+                safe: pipeline_config.safe,
+                read_batch: pipeline_config.read_batch,
             });
         }
         
@@ -29,12 +44,44 @@ impl Config {
         Ok(config)
     }
 
+    /// Validate the supplied input values and add them, or their defaults,
+    /// to the config's variables
+    pub fn apply_inputs(&mut self, provided: &HashMap<String, Literal>) -> anyhow::Result<()> {
+        let resolved = resolve_inputs(&self.inputs, provided)?;
+        for (name, variable) in resolved {
+            if self.variables.contains_key(&name) {
+                anyhow::bail!("Input {} conflicts with a variable of the same name", name);
+            }
+            self.variables.insert(name, variable);
+        }
+        Ok(())
+    }
+
+    /// Replace or add variables from a `name: { ty, value }` file, such as
+    /// one written by `mine-salt`
+    pub fn apply_overrides(&mut self, path: &Path) -> anyhow::Result<()> {
+        let contents = fs::read_to_string(path)?;
+        let overrides: VariableData = serde_yaml::from_str(&contents)?;
+        self.variables.extend(overrides.variables);
+        Ok(())
+    }
+
     pub fn new() -> Config {
         Config {
             variables: HashMap::new(),
+            inputs: HashMap::new(),
             data: HashMap::new(),
             actions: Vec::new(),
+            verification: None,
+            safe: None,
+            read_batch: None,
         }
     }
 }
 
+/// Write variables as an override file for `Config::apply_overrides`
+pub fn write_overrides(path: &Path, variables: HashMap<String, Variable>) -> anyhow::Result<()> {
+    let contents = serde_yaml::to_string(&VariableData { variables })?;
+    fs::write(path, contents)?;
+    Ok(())
+}
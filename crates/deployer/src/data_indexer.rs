@@ -1,6 +1,9 @@
-use crate::indexer::Indexer;
+use crate::{config::config::Config, indexer::Indexer};
 use alloy::dyn_abi::DynSolValue;
-use deployer_core::{DataReference, FileDataResolver, HierarchicalVariableResolver, Result, Variable, VariableResolver};
+use deployer_core::{
+    ContractData, DataReference, DataResolver, FileDataResolver, HierarchicalVariableResolver, Result, Secret,
+    SecretStore, Variable, VariableResolver,
+};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -22,15 +25,42 @@ impl DataIndexer {
         data_refs: HashMap<String, DataReference>,
         local_variables: HashMap<String, Variable>,
         data_dir: PathBuf,
+        secrets: SecretStore,
     ) -> Self {
         Self {
             base_indexer: Indexer::new(),
             data_refs,
             local_variables,
-            data_resolver: FileDataResolver::new(data_dir),
+            data_resolver: FileDataResolver::new(data_dir).with_secrets(secrets),
         }
     }
 
+    /// Build an indexer for a config, with its variables saved up front
+    pub fn for_config(
+        config: &Config,
+        data_dir: PathBuf,
+        secrets: SecretStore,
+    ) -> anyhow::Result<Self> {
+        let mut data_indexer = Self::new(
+            config.data.clone(),
+            config.variables.clone(),
+            data_dir,
+            secrets,
+        );
+
+        // Also save variables to the base indexer for backward compatibility.
+        // Secrets are left out so their plaintext is never held here; they
+        // resolve through `get_secret` each time they're used
+        for (k, v) in &config.variables {
+            if v.value.contains_secret() {
+                continue;
+            }
+            data_indexer.save_variable(k, v)?;
+        }
+
+        Ok(data_indexer)
+    }
+
     /// Get the base indexer for direct access
     pub fn base_indexer(&self) -> &Indexer {
         &self.base_indexer
@@ -42,8 +72,9 @@ impl DataIndexer {
     }
 
     /// Save variable to the base indexer
-    pub fn save_variable(&mut self, key: &str, ty: &str, value: &str) -> anyhow::Result<()> {
-        self.base_indexer.save_variable(key, ty, value)
+    pub fn save_variable(&mut self, key: &str, variable: &Variable) -> anyhow::Result<()> {
+        let value = variable.resolve(self)?;
+        self.base_indexer.save_variable_value(key, value)
     }
 
     /// Save output data to the base indexer
@@ -92,4 +123,28 @@ impl VariableResolver for DataIndexer {
         
         hierarchical_resolver.get_data(path)
     }
-}
\ No newline at end of file
+
+    fn get_contract(&self, key: &str) -> Result<ContractData> {
+        let hierarchical_resolver = HierarchicalVariableResolver::new(
+            &self.local_variables,
+            &self.data_refs,
+            &self.data_resolver,
+        );
+
+        hierarchical_resolver.get_contract(key)
+    }
+
+    fn get_secret(&self, name: &str) -> Result<Secret> {
+        self.data_resolver.get_secret(name)
+    }
+
+    fn is_secret_variable(&self, key: &str) -> bool {
+        let hierarchical_resolver = HierarchicalVariableResolver::new(
+            &self.local_variables,
+            &self.data_refs,
+            &self.data_resolver,
+        );
+
+        hierarchical_resolver.is_secret_variable(key)
+    }
+}
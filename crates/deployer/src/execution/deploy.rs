@@ -1,16 +1,21 @@
 use alloy::{
-    dyn_abi::{DynSolType, DynSolValue, JsonAbiExt},
-    json_abi::Constructor,
-    primitives::{keccak256, Address, Bytes, FixedBytes},
+    dyn_abi::{DynSolType, DynSolValue},
+    json_abi::{Constructor, JsonAbi},
+    primitives::{Address, Bytes, FixedBytes},
     providers::{network::TransactionBuilder, Provider},
-    rpc::types::TransactionRequest,
+    rpc::types::{TransactionReceipt, TransactionRequest},
+};
+use super::RevertDecoder;
+use crate::utils::generate_initcode;
+use deployer_core::{
+    link_bytecode, ContractData, DeployerError, DeploymentData, LinkReferences, VariableResolver,
+    VariableValue,
 };
-use deployer_core::{DeploymentData, VariableResolver, VariableValue};
 use hex;
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 // Standard CREATE2 deployer address (deterministic deployment proxy)
-const CREATE2_DEPLOYER: Address = Address::new([
+pub const CREATE2_DEPLOYER: Address = Address::new([
     0x4e, 0x59, 0xb4, 0x48, 0x47, 0xb3, 0x79, 0x57, 0x85, 0x88,
     0x92, 0x0c, 0xa7, 0x8f, 0xbf, 0x26, 0xc0, 0xb4, 0x95, 0x6c
 ]);
@@ -31,147 +36,69 @@ where
         &self,
         data: &DeploymentData,
         resolver: &R,
-    ) -> anyhow::Result<(Address, FixedBytes<32>)> {
-        // Resolve the expected deployment address
-        let expected_address = data
-            .address
-            .resolve(DynSolType::Address, resolver)
-            .map_err(|e| anyhow::anyhow!("Failed to resolve expected address: {:?}", e))?
-            .as_address()
-            .ok_or_else(|| anyhow::anyhow!("Failed to resolve expected address"))?;
-
-        // Resolve salt
-        let salt_value = data
-            .salt
-            .resolve(DynSolType::FixedBytes(32), resolver)
-            .map_err(|e| anyhow::anyhow!("Failed to resolve salt: {:?}", e))?;
-        let salt_bytes = salt_value
-            .as_fixed_bytes()
-            .ok_or_else(|| anyhow::anyhow!("Failed to resolve salt as bytes32"))?;
-        let salt = FixedBytes::<32>::try_from(salt_bytes.0)
-            .map_err(|_| anyhow::anyhow!("Invalid salt length"))?;
-
-        // Resolve bytecode
-        let bytecode = match &data.bytecode {
-            VariableValue::Value(v) => {
-                // For direct string values, decode from hex
-                Bytes::from(hex::decode(v.trim_start_matches("0x"))?)
-            }
-            VariableValue::Var(key) => {
-                let bytecode_value = resolver
-                    .get_variable(key)
-                    .map_err(|e| anyhow::anyhow!("Failed to resolve bytecode variable: {:?}", e))?;
-
-                // Check if it's already bytes
-                if let Some(bytes) = bytecode_value.as_bytes() {
-                    Bytes::from(bytes.to_vec())
-                } else if let Some(str_val) = bytecode_value.as_str() {
-                    // If it's a string, decode from hex
-                    Bytes::from(hex::decode(str_val.trim_start_matches("0x"))?)
-                } else {
-                    anyhow::bail!("Bytecode variable must be bytes or hex string")
-                }
-            }
-            VariableValue::Output(id) => {
-                let bytecode_value = resolver
-                    .get_output(id)
-                    .map_err(|e| anyhow::anyhow!("Failed to resolve bytecode output: {:?}", e))?;
-
-                if let Some(bytes) = bytecode_value.as_bytes() {
-                    Bytes::from(bytes.to_vec())
-                } else if let Some(str_val) = bytecode_value.as_str() {
-                    Bytes::from(hex::decode(str_val.trim_start_matches("0x"))?)
-                } else {
-                    anyhow::bail!("Bytecode output must be bytes or hex string")
-                }
-            }
-            VariableValue::Data(path) => {
-                let bytecode_value = resolver
-                    .get_data(path)
-                    .map_err(|e| anyhow::anyhow!("Failed to resolve bytecode data: {:?}", e))?;
-
-                if let Some(bytes) = bytecode_value.as_bytes() {
-                    Bytes::from(bytes.to_vec())
-                } else if let Some(str_val) = bytecode_value.as_str() {
-                    Bytes::from(hex::decode(str_val.trim_start_matches("0x"))?)
-                } else {
-                    anyhow::bail!("Bytecode data must be bytes or hex string")
-                }
-            }
-        };
-
-        let constructor: Constructor = data.constructor_abi_item.parse()?;
-        let args: Vec<DynSolValue> = data
-            .constructor_args
-            .iter()
-            .enumerate()
-            .map(|(i, arg)| {
-                let sol_type = DynSolType::parse(&constructor.inputs[i].ty)
-                    .map_err(|e| anyhow::anyhow!("Invalid constructor arg type: {}", e))?;
-                arg.resolve(sol_type, resolver)
-                    .map_err(|e| anyhow::anyhow!("Failed to resolve constructor arg: {:?}", e))
-            })
-            .collect::<anyhow::Result<Vec<_>>>()?;
-
-        let encoded_args = constructor.abi_encode_input(&args)?;
-
-        let mut initcode = bytecode.to_vec();
-        initcode.extend_from_slice(&encoded_args);
-        let initcode = Bytes::from(initcode);
-        
-        // Calculate the CREATE2 address using the standard deployer
-        let create2_address = self.calculate_create2_address(CREATE2_DEPLOYER, salt, &initcode);
-        
-        // Verify that the computed address matches the expected address
-        if create2_address != expected_address {
-            anyhow::bail!(
-                "CREATE2 address mismatch! Expected: 0x{:x}, Computed: 0x{:x}",
-                expected_address,
-                create2_address
-            );
-        }
+    ) -> anyhow::Result<(Address, Option<TransactionReceipt>)> {
+        let Create2Deployment {
+            factory,
+            salt,
+            initcode,
+            address: create2_address,
+        } = predict_deployment(data, resolver)?;
+        let initcode = initcode.code;
 
         // Check if contract already exists at this address
         let code = self.provider.get_code_at(create2_address).await?;
         if !code.is_empty() {
             println!("Contract already deployed at: 0x{:x}", create2_address);
-            // Return the existing address with a zero hash to indicate no new deployment
-            return Ok((create2_address, FixedBytes::ZERO));
+            // No receipt indicates no new deployment
+            return Ok((create2_address, None));
         }
 
         // Build CREATE2 deployment transaction
-        let deployment_tx = self.build_create2_transaction(CREATE2_DEPLOYER, salt, initcode)?;
+        let deployment_tx =
+            self.build_create2_transaction(factory, salt, initcode.clone())?;
+
+        // Simulate first so constructor reverts can be decoded before anything is sent
+        let decoder = match data.contract.as_deref() {
+            Some(contract) => RevertDecoder::for_action(Some(contract), &data.errors, resolver)?,
+            // `bytecode: !data X.…` may name a contract artifact, or a raw or
+            // variables reference with no ABI, in which case only `errors` are used
+            None => {
+                let bytecode_ref = match &data.bytecode {
+                    Some(VariableValue::Data(path)) => path.split('.').next(),
+                    _ => None,
+                };
+                RevertDecoder::for_action(bytecode_ref, &data.errors, resolver)
+                    .or_else(|_| RevertDecoder::for_action(None, &data.errors, resolver))?
+            }
+        };
+        if let Err(e) = self.provider.call(deployment_tx.clone()).await {
+            // The CREATE2 deployer swallows the constructor's revert data, so
+            // replay the initcode as a plain CREATE to recover it
+            let create_tx = TransactionRequest::default().with_deploy_code(initcode);
+            let error = match self.provider.call(create_tx).await {
+                Err(create_error) if RevertDecoder::revert_data(&create_error).is_some() => {
+                    create_error
+                }
+                _ => e,
+            };
+            return Err(decoder.transaction_failed("Deployment", error));
+        }
 
         // Send transaction
         let pending_tx = self.provider.send_transaction(deployment_tx).await?;
         let receipt = pending_tx.get_receipt().await?;
 
         if !receipt.status() {
-            anyhow::bail!("Deployment transaction failed");
+            return Err(DeployerError::TransactionFailed(format!(
+                "Deployment transaction 0x{:x} reverted",
+                receipt.transaction_hash
+            ))
+            .into());
         }
 
-        let tx_hash = receipt.transaction_hash;
         println!("Contract deployed at: 0x{:x}", create2_address);
 
-        Ok((create2_address, tx_hash))
-    }
-
-    fn calculate_create2_address(
-        &self,
-        deployer: Address,
-        salt: FixedBytes<32>,
-        initcode: &Bytes,
-    ) -> Address {
-        let initcode_hash = keccak256(initcode);
-
-        let mut data = Vec::with_capacity(1 + 20 + 32 + 32);
-        data.push(0xff);
-        data.extend_from_slice(deployer.as_slice());
-        data.extend_from_slice(salt.as_slice());
-        data.extend_from_slice(initcode_hash.as_slice());
-
-        let hash = keccak256(&data);
-        Address::from_slice(&hash[12..])
+        Ok((create2_address, Some(receipt)))
     }
 
     fn build_create2_transaction(
@@ -192,3 +119,221 @@ where
         Ok(tx)
     }
 }
+
+/// Everything needed to send a CREATE2 deployment, computed off-chain
+#[derive(Debug, Clone)]
+pub struct Create2Deployment {
+    pub factory: Address,
+    pub salt: FixedBytes<32>,
+    pub initcode: Initcode,
+    pub address: Address,
+}
+
+/// Compute the CREATE2 address of a deploy action without touching the chain,
+/// checking it against the action's `address` when one is given
+pub fn predict_deployment<R: VariableResolver>(
+    data: &DeploymentData,
+    resolver: &R,
+) -> anyhow::Result<Create2Deployment> {
+    let salt = resolve_salt(data, resolver)?;
+    let initcode = build_initcode(data, resolver)?;
+    let address = CREATE2_DEPLOYER.create2_from_code(salt, &initcode.code);
+
+    if let Some(expected) = &data.address {
+        let expected_address = expected
+            .resolve(DynSolType::Address, resolver)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve expected address: {:?}", e))?
+            .as_address()
+            .ok_or_else(|| anyhow::anyhow!("Failed to resolve expected address"))?;
+
+        if address != expected_address {
+            anyhow::bail!(
+                "CREATE2 address mismatch! Expected: 0x{:x}, Computed: 0x{:x}",
+                expected_address,
+                address
+            );
+        }
+    }
+
+    Ok(Create2Deployment {
+        factory: CREATE2_DEPLOYER,
+        salt,
+        initcode,
+        address,
+    })
+}
+
+fn resolve_salt<R: VariableResolver>(
+    data: &DeploymentData,
+    resolver: &R,
+) -> anyhow::Result<FixedBytes<32>> {
+    let salt_value = data
+        .salt
+        .resolve(DynSolType::FixedBytes(32), resolver)
+        .map_err(|e| anyhow::anyhow!("Failed to resolve salt: {:?}", e))?;
+    let salt_bytes = salt_value
+        .as_fixed_bytes()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve salt as bytes32"))?;
+    FixedBytes::<32>::try_from(salt_bytes.0).map_err(|_| anyhow::anyhow!("Invalid salt length"))
+}
+
+/// Creation bytecode followed by the ABI-encoded constructor args
+#[derive(Debug, Clone)]
+pub struct Initcode {
+    pub code: Bytes,
+    bytecode_len: usize,
+}
+
+impl Initcode {
+    /// The ABI-encoded constructor args appended to the bytecode
+    pub fn constructor_args(&self) -> Bytes {
+        self.code.slice(self.bytecode_len..)
+    }
+}
+
+/// Build the initcode for a deploy action from its bytecode and ABI-encoded
+/// constructor args, without touching the chain
+pub fn build_initcode<R: VariableResolver>(
+    data: &DeploymentData,
+    resolver: &R,
+) -> anyhow::Result<Initcode> {
+    let contract = match &data.contract {
+        Some(key) => Some(
+            resolver
+                .get_contract(key)
+                .map_err(|e| anyhow::anyhow!("Failed to load contract {}: {}", key, e))?,
+        ),
+        None => None,
+    };
+
+    // An explicit constructor signature takes precedence over the contract ABI
+    let abi = match (&data.constructor_abi_item, &contract) {
+        (Some(item), _) => {
+            let constructor: Constructor = item.parse()?;
+            JsonAbi {
+                constructor: Some(constructor),
+                ..Default::default()
+            }
+        }
+        (None, Some(contract)) => contract.json_abi()?,
+        (None, None) => JsonAbi::default(),
+    };
+
+    let inputs = abi.constructor.as_ref().map(|c| c.inputs.as_slice()).unwrap_or_default();
+    if inputs.len() != data.constructor_args.len() {
+        anyhow::bail!(
+            "Constructor expects {} args but {} were provided",
+            inputs.len(),
+            data.constructor_args.len()
+        );
+    }
+
+    let args: Vec<DynSolValue> = data
+        .constructor_args
+        .iter()
+        .zip(inputs)
+        .map(|(arg, input)| {
+            arg.resolve_param(input, resolver)
+                .map_err(|e| anyhow::anyhow!("Failed to resolve constructor arg: {:?}", e))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let libraries = data
+        .libraries
+        .iter()
+        .map(|(name, value)| {
+            let address = value
+                .resolve(DynSolType::Address, resolver)
+                .map_err(|e| anyhow::anyhow!("Failed to resolve library {}: {:?}", name, e))?
+                .as_address()
+                .ok_or_else(|| anyhow::anyhow!("Library {} must resolve to an address", name))?;
+            Ok((name.clone(), address))
+        })
+        .collect::<anyhow::Result<HashMap<_, _>>>()?;
+
+    let bytecode = match (&data.bytecode, &contract) {
+        (Some(bytecode), _) => {
+            let code = resolve_bytecode(bytecode, resolver)?;
+            let link_references = explicit_link_references(bytecode, contract.as_ref(), &code, resolver);
+            link_bytecode(&code, &link_references, &libraries)?
+        }
+        (None, Some(contract)) => {
+            link_bytecode(&contract.bytecode, &contract.link_references, &libraries)?
+        }
+        (None, None) => anyhow::bail!("Deploy action must specify either bytecode or contract"),
+    };
+    let bytecode = Bytes::from(hex::decode(bytecode.trim_start_matches("0x"))?);
+    let bytecode_len = bytecode.len();
+
+    Ok(Initcode {
+        code: generate_initcode(abi, bytecode, args)?,
+        bytecode_len,
+    })
+}
+
+/// Link references for explicit bytecode, taken from `contract` or the contract
+/// behind `bytecode: !data <contract>.…`. Without them, libraries can only be
+/// linked by fully qualified name
+fn explicit_link_references<R: VariableResolver>(
+    bytecode: &VariableValue,
+    contract: Option<&ContractData>,
+    code: &str,
+    resolver: &R,
+) -> LinkReferences {
+    let referenced = match (contract, bytecode) {
+        (Some(_), _) => None,
+        (None, VariableValue::Data(path)) => path
+            .split('.')
+            .next()
+            .and_then(|key| resolver.get_contract(key).ok()),
+        (None, _) => None,
+    };
+    let Some(contract) = contract.or(referenced.as_ref()) else {
+        return LinkReferences::new();
+    };
+
+    // Only trust the references if they point at placeholders in this bytecode
+    let code = code.trim_start_matches("0x");
+    let matches = contract.link_references.values().flat_map(|libs| libs.values()).flatten().all(
+        |reference| code.get(reference.start * 2..).is_some_and(|rest| rest.starts_with("__")),
+    );
+    if matches {
+        contract.link_references.clone()
+    } else {
+        LinkReferences::new()
+    }
+}
+
+/// Resolve bytecode to a hex string, which may still contain library placeholders
+fn resolve_bytecode<R: VariableResolver>(
+    bytecode: &VariableValue,
+    resolver: &R,
+) -> anyhow::Result<String> {
+    let bytecode_value = match bytecode {
+        VariableValue::Value(v) => return Ok(v.clone()),
+        VariableValue::Var(key) => resolver
+            .get_variable(key)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve bytecode variable: {:?}", e))?,
+        VariableValue::Output(id) => resolver
+            .get_output(id)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve bytecode output: {:?}", e))?,
+        VariableValue::Data(path) => resolver
+            .get_data(path)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve bytecode data: {:?}", e))?,
+        VariableValue::Expr(expr) => deployer_core::evaluate(expr, resolver)
+            .map_err(|e| anyhow::anyhow!("Failed to evaluate bytecode expression: {:?}", e))?,
+        // Read as a string so unlinked placeholders survive
+        other => other
+            .resolve(DynSolType::String, resolver)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve bytecode: {:?}", e))?,
+    };
+
+    // Accept either raw bytes or a hex string
+    if let Some(bytes) = bytecode_value.as_bytes() {
+        Ok(format!("0x{}", hex::encode(bytes)))
+    } else if let Some(str_val) = bytecode_value.as_str() {
+        Ok(str_val.to_string())
+    } else {
+        anyhow::bail!("Bytecode must be bytes or hex string")
+    }
+}
@@ -1,7 +1,24 @@
 pub mod deploy;
+pub mod multicall;
+pub mod proxy;
+pub mod raw;
 pub mod read;
+pub mod revert;
+pub mod storage;
+pub mod transfer;
 pub mod write;
 
-pub use deploy::DeploymentExecutor;
-pub use read::ReadExecutor;
-pub use write::WriteExecutor;
\ No newline at end of file
+pub use deploy::{
+    build_initcode, predict_deployment, Create2Deployment, DeploymentExecutor, Initcode,
+    CREATE2_DEPLOYER,
+};
+pub use multicall::{validate_batch, BatchCallResult, MulticallExecutor, MULTICALL3};
+pub use proxy::{
+    new_implementation_contract, proxy_deployment_data, ProxyExecutor, ADMIN_SLOT, BEACON_SLOT, IMPLEMENTATION_SLOT,
+};
+pub use raw::RawExecutor;
+pub use read::{build_read_call, ReadCall, ReadExecutor};
+pub use revert::RevertDecoder;
+pub use storage::{storage_slot, StorageReadExecutor};
+pub use transfer::TransferExecutor;
+pub use write::{build_call, validate_write, WriteCall, WriteExecutor};
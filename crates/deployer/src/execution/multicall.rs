@@ -0,0 +1,284 @@
+use alloy::{
+    dyn_abi::{DynSolType, DynSolValue, FunctionExt, JsonAbiExt},
+    json_abi::Function,
+    primitives::{address, Address, Bytes, U256},
+    providers::{network::TransactionBuilder, Provider},
+    rpc::types::{BlockId, TransactionReceipt, TransactionRequest},
+};
+use super::{build_call, build_read_call, validate_write, RevertDecoder, WriteCall};
+use deployer_core::{
+    BatchData, DeployerError, ReadBatchConfig, ReadData, VariableResolver, VariableValue,
+};
+use std::{str::FromStr, sync::Arc};
+
+/// Canonical Multicall3 deployment, at the same address on most chains
+pub const MULTICALL3: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+const AGGREGATE3: &str = "function aggregate3((address,bool,bytes)[] calls) payable returns ((bool,bytes)[] returnData)";
+const AGGREGATE3_VALUE: &str = "function aggregate3Value((address,bool,uint256,bytes)[] calls) payable returns ((bool,bytes)[] returnData)";
+
+/// Check a batch before the run starts. Its calls all go through Multicall3
+/// from the pipeline's key, so none of them can set its own `sender`
+pub fn validate_batch<R: VariableResolver>(data: &BatchData, resolver: &R) -> anyhow::Result<()> {
+    for call in &data.calls {
+        if call.write.sender.is_some() {
+            anyhow::bail!(
+                "Batch call {} sets sender, but batch calls are always sent through Multicall3 from the pipeline's key",
+                call.id
+            );
+        }
+        validate_write(&call.write, resolver)
+            .map_err(|e| anyhow::anyhow!("Batch call {}: {}", call.id, e))?;
+    }
+    Ok(())
+}
+
+/// Outcome of one call in a batch
+#[derive(Debug, Clone)]
+pub struct BatchCallResult {
+    pub id: String,
+    pub function: Function,
+    pub success: bool,
+    pub return_data: Bytes,
+}
+
+pub struct MulticallExecutor<P> {
+    provider: Arc<P>,
+}
+
+impl<P> MulticallExecutor<P>
+where
+    P: Provider,
+{
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider }
+    }
+
+    /// Send a batch of writes in one `aggregate3Value` transaction. Return data
+    /// comes from simulating the batch just before it is sent
+    pub async fn write_batch<R: VariableResolver>(
+        &self,
+        data: &BatchData,
+        resolver: &R,
+    ) -> anyhow::Result<(TransactionReceipt, Vec<BatchCallResult>)> {
+        let multicall = multicall_address(data.multicall.as_ref(), resolver)?;
+        let calls = data
+            .calls
+            .iter()
+            .map(|call| build_call(&call.write, resolver))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let function: Function = AGGREGATE3_VALUE.parse()?;
+
+        // Simulate with every call allowed to fail so a failing call can be
+        // reported on its own rather than as a failed batch
+        let simulation = aggregate3_value_tx(&function, multicall, &calls, |_| true)?;
+        let output = self
+            .provider
+            .call(simulation)
+            .await
+            .map_err(|e| RevertDecoder::default().transaction_failed("aggregate3Value", e))?;
+        let outcomes = decode_results(&function, &output)?;
+
+        let mut results = Vec::with_capacity(calls.len());
+        for ((batch_call, call), (success, return_data)) in data.calls.iter().zip(&calls).zip(outcomes) {
+            if !success {
+                let decoder = RevertDecoder::for_action(
+                    batch_call.write.contract.as_deref(),
+                    &batch_call.write.errors,
+                    resolver,
+                )?;
+                let reason = decoder.decode(&return_data);
+                if !batch_call.allow_failure {
+                    return Err(DeployerError::TransactionFailed(format!(
+                        "Batch call {} ({}) would revert: {}",
+                        batch_call.id, call.function.name, reason
+                    ))
+                    .into());
+                }
+                println!("Batch call {} is expected to revert: {}", batch_call.id, reason);
+            }
+            results.push(BatchCallResult {
+                id: batch_call.id.clone(),
+                function: call.function.clone(),
+                success,
+                return_data,
+            });
+        }
+
+        let tx = aggregate3_value_tx(&function, multicall, &calls, |i| data.calls[i].allow_failure)?;
+        let receipt = self.provider.send_transaction(tx).await?.get_receipt().await?;
+        if !receipt.status() {
+            return Err(DeployerError::TransactionFailed(format!(
+                "aggregate3Value: transaction 0x{:x} reverted",
+                receipt.transaction_hash
+            ))
+            .into());
+        }
+
+        println!("{:?}", receipt);
+
+        Ok((receipt, results))
+    }
+
+    /// Make several reads through `aggregate3`, `config.batch_size` at a time.
+    /// Returns each read's id, function and decoded outputs in input order; a
+    /// failing read doesn't stop the others and all failures are reported together
+    pub async fn read_batch<R: VariableResolver>(
+        &self,
+        reads: &[(String, &ReadData)],
+        config: &ReadBatchConfig,
+        resolver: &R,
+    ) -> anyhow::Result<Vec<(String, Function, Vec<DynSolValue>)>> {
+        let multicall = multicall_address(config.multicall.as_ref(), resolver)?;
+        let block = match &config.block {
+            Some(block) => BlockId::from_str(block)
+                .map_err(|e| anyhow::anyhow!("Invalid read block {}: {}", block, e))?,
+            None => BlockId::latest(),
+        };
+        let function: Function = AGGREGATE3.parse()?;
+        let calls = reads
+            .iter()
+            .map(|(_, data)| build_read_call(data, resolver))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut results = Vec::with_capacity(reads.len());
+        let mut failures = Vec::new();
+        for (reads, calls) in reads
+            .chunks(config.batch_size.max(1))
+            .zip(calls.chunks(config.batch_size.max(1)))
+        {
+            let entries = calls
+                .iter()
+                .map(|call| {
+                    DynSolValue::Tuple(vec![
+                        DynSolValue::Address(call.to),
+                        DynSolValue::Bool(true),
+                        DynSolValue::Bytes(call.input.to_vec()),
+                    ])
+                })
+                .collect();
+            let tx = TransactionRequest::default()
+                .with_to(multicall)
+                .with_input(Bytes::from(
+                    function.abi_encode_input(&[DynSolValue::Array(entries)])?,
+                ));
+            let output = self
+                .provider
+                .call(tx)
+                .block(block)
+                .await
+                .map_err(|e| RevertDecoder::default().transaction_failed("aggregate3", e))?;
+            let outcomes = decode_results(&function, &output)?;
+            if outcomes.len() != calls.len() {
+                anyhow::bail!(
+                    "aggregate3 returned {} results for {} reads",
+                    outcomes.len(),
+                    calls.len()
+                );
+            }
+
+            for (((id, data), call), (success, return_data)) in
+                reads.iter().zip(calls).zip(outcomes)
+            {
+                if !success {
+                    let decoder =
+                        RevertDecoder::for_action(data.contract.as_deref(), &[], resolver)?;
+                    failures.push(format!(
+                        "{} ({}): {}",
+                        id,
+                        call.function.name,
+                        decoder.decode(&return_data)
+                    ));
+                    continue;
+                }
+                if return_data.is_empty() && !call.function.outputs.is_empty() {
+                    failures.push(format!(
+                        "{} ({}): no contract deployed at address 0x{:x}",
+                        id, call.function.name, call.to
+                    ));
+                    continue;
+                }
+                match call.function.abi_decode_output(&return_data) {
+                    Ok(values) => {
+                        println!("output {:?}", values);
+                        results.push((id.clone(), call.function.clone(), values));
+                    }
+                    Err(e) => failures.push(format!(
+                        "{} ({}): failed to decode output: {}",
+                        id, call.function.name, e
+                    )),
+                }
+            }
+        }
+
+        if !failures.is_empty() {
+            return Err(DeployerError::Execution(format!(
+                "Batched reads failed:\n  - {}",
+                failures.join("\n  - ")
+            ))
+            .into());
+        }
+
+        Ok(results)
+    }
+}
+
+fn multicall_address<R: VariableResolver>(
+    multicall: Option<&VariableValue>,
+    resolver: &R,
+) -> anyhow::Result<Address> {
+    match multicall {
+        Some(multicall) => multicall
+            .resolve(DynSolType::Address, resolver)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve multicall address: {:?}", e))?
+            .as_address()
+            .ok_or_else(|| anyhow::anyhow!("Failed to resolve multicall address")),
+        None => Ok(MULTICALL3),
+    }
+}
+
+fn aggregate3_value_tx(
+    function: &Function,
+    multicall: Address,
+    calls: &[WriteCall],
+    allow_failure: impl Fn(usize) -> bool,
+) -> anyhow::Result<TransactionRequest> {
+    let entries = calls
+        .iter()
+        .enumerate()
+        .map(|(i, call)| {
+            DynSolValue::Tuple(vec![
+                DynSolValue::Address(call.to),
+                DynSolValue::Bool(allow_failure(i)),
+                DynSolValue::Uint(call.value, 256),
+                DynSolValue::Bytes(call.input.to_vec()),
+            ])
+        })
+        .collect();
+    let value = calls.iter().fold(U256::ZERO, |total, call| total + call.value);
+    let input = function.abi_encode_input(&[DynSolValue::Array(entries)])?;
+
+    Ok(TransactionRequest::default()
+        .with_to(multicall)
+        .with_value(value)
+        .with_input(Bytes::from(input)))
+}
+
+/// Decode Multicall3's `(bool success, bytes returnData)[]` results
+fn decode_results(function: &Function, output: &[u8]) -> anyhow::Result<Vec<(bool, Bytes)>> {
+    let decoded = function.abi_decode_output(output)?;
+    let results = decoded
+        .first()
+        .and_then(DynSolValue::as_array)
+        .ok_or_else(|| anyhow::anyhow!("Unexpected multicall output"))?;
+
+    results
+        .iter()
+        .map(|result| match result.as_tuple() {
+            Some([DynSolValue::Bool(success), DynSolValue::Bytes(data)]) => {
+                Ok((*success, Bytes::from(data.clone())))
+            }
+            _ => Err(anyhow::anyhow!("Unexpected multicall result {:?}", result)),
+        })
+        .collect()
+}
@@ -0,0 +1,239 @@
+use crate::utils::resolve_function;
+use alloy::{
+    contract::CallBuilder,
+    dyn_abi::{DynSolValue, JsonAbiExt},
+    json_abi::Function,
+    primitives::{b256, Address, Bytes, B256},
+    providers::Provider,
+};
+use deployer_core::{
+    DeploymentData, ProxyDeploymentData, ProxyKind, ProxyUpgradeData, VariableResolver,
+    VariableValue, WriteData,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// ERC-1967 implementation slot, `bytes32(uint256(keccak256('eip1967.proxy.implementation')) - 1)`
+pub const IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+/// ERC-1967 admin slot, `bytes32(uint256(keccak256('eip1967.proxy.admin')) - 1)`
+pub const ADMIN_SLOT: B256 =
+    b256!("b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103");
+/// ERC-1967 beacon slot, `bytes32(uint256(keccak256('eip1967.proxy.beacon')) - 1)`
+pub const BEACON_SLOT: B256 =
+    b256!("a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50");
+
+pub struct ProxyExecutor<P> {
+    provider: Arc<P>,
+}
+
+impl<P> ProxyExecutor<P>
+where
+    P: Provider,
+{
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider }
+    }
+
+    /// Read an address stored in one of the ERC-1967 slots of `proxy`
+    pub async fn read_slot_address(&self, proxy: Address, slot: B256) -> anyhow::Result<Address> {
+        let value = self.provider.get_storage_at(proxy, slot.into()).await?;
+        Ok(Address::from_word(value.into()))
+    }
+
+    /// The implementation currently used by `proxy`; for beacon proxies this
+    /// is the beacon's implementation
+    pub async fn current_implementation(
+        &self,
+        kind: ProxyKind,
+        proxy: Address,
+    ) -> anyhow::Result<Address> {
+        match kind {
+            ProxyKind::Beacon => {
+                let beacon = self.read_slot_address(proxy, BEACON_SLOT).await?;
+                self.beacon_implementation(beacon).await
+            }
+            _ => self.read_slot_address(proxy, IMPLEMENTATION_SLOT).await,
+        }
+    }
+
+    pub async fn beacon_implementation(&self, beacon: Address) -> anyhow::Result<Address> {
+        let function: Function = "function implementation() view returns (address)".parse()?;
+        let output = CallBuilder::new_raw(
+            self.provider.clone(),
+            Bytes::from(function.abi_encode_input(&[])?),
+        )
+        .to(beacon)
+        .call_raw()
+        .with_decoder(&function)
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to read implementation of beacon 0x{:x}: {}",
+                beacon,
+                e
+            )
+        })?;
+
+        output
+            .first()
+            .and_then(DynSolValue::as_address)
+            .ok_or_else(|| anyhow::anyhow!("Beacon 0x{:x} returned no implementation", beacon))
+    }
+
+    /// Build the write that points `proxy` at `implementation`. Transparent
+    /// proxies are upgraded through the `ProxyAdmin` read from the admin slot
+    pub async fn upgrade_write<R: VariableResolver>(
+        &self,
+        data: &ProxyUpgradeData,
+        proxy: Address,
+        implementation: Address,
+        resolver: &R,
+    ) -> anyhow::Result<WriteData> {
+        let contract = new_implementation_contract(data);
+        let call = encode_call(data.call.as_deref(), contract, &data.call_args, resolver)?;
+
+        let (target, abi_item, args) = match data.kind {
+            ProxyKind::Erc1967 | ProxyKind::Uups => (
+                proxy,
+                "function upgradeToAndCall(address newImplementation, bytes data) payable",
+                vec![address_value(implementation), bytes_value(&call)],
+            ),
+            ProxyKind::Transparent => (
+                self.read_slot_address(proxy, ADMIN_SLOT).await?,
+                "function upgradeAndCall(address proxy, address implementation, bytes data) payable",
+                vec![address_value(proxy), address_value(implementation), bytes_value(&call)],
+            ),
+            ProxyKind::Beacon => {
+                if !call.is_empty() {
+                    anyhow::bail!("Beacon upgrades cannot make a call on the new implementation");
+                }
+                (
+                    self.read_slot_address(proxy, BEACON_SLOT).await?,
+                    "function upgradeTo(address newImplementation)",
+                    vec![address_value(implementation)],
+                )
+            }
+        };
+
+        Ok(WriteData {
+            address: address_value(target),
+            abi_item: Some(abi_item.to_string()),
+            args,
+            value: VariableValue::Value("0".to_string()),
+            contract: contract.map(str::to_string),
+            function: None,
+            errors: data.errors.clone(),
+            sender: None,
+        })
+    }
+}
+
+/// Deployment of the proxy contract itself, with constructor args for its kind
+/// wrapping the encoded initializer call
+pub fn proxy_deployment_data<R: VariableResolver>(
+    data: &ProxyDeploymentData,
+    implementation: Option<Address>,
+    resolver: &R,
+) -> anyhow::Result<DeploymentData> {
+    let contract = data
+        .implementation
+        .as_deref()
+        .and_then(|i| i.contract.as_deref());
+    let initializer = bytes_value(&encode_call(
+        data.initializer.as_deref(),
+        contract,
+        &data.initializer_args,
+        resolver,
+    )?);
+    let implementation = || {
+        implementation.map(address_value).ok_or_else(|| {
+            anyhow::anyhow!("Proxy requires implementation or implementation_address")
+        })
+    };
+
+    let (constructor, constructor_args) = match data.kind {
+        ProxyKind::Erc1967 | ProxyKind::Uups => (
+            "constructor(address implementation, bytes _data)",
+            vec![implementation()?, initializer],
+        ),
+        ProxyKind::Transparent => {
+            let admin = data
+                .admin
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("Transparent proxy requires admin"))?;
+            (
+                "constructor(address _logic, address initialOwner, bytes _data)",
+                vec![implementation()?, admin, initializer],
+            )
+        }
+        ProxyKind::Beacon => {
+            let beacon = data
+                .beacon
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("Beacon proxy requires beacon"))?;
+            (
+                "constructor(address beacon, bytes data)",
+                vec![beacon, initializer],
+            )
+        }
+    };
+
+    Ok(DeploymentData {
+        address: data.proxy.address.clone(),
+        constructor_args,
+        salt: data.proxy.salt.clone(),
+        constructor_abi_item: Some(constructor.to_string()),
+        bytecode: data.proxy.bytecode.clone(),
+        contract: data.proxy.contract.clone(),
+        errors: data.proxy.errors.clone(),
+        libraries: HashMap::new(),
+    })
+}
+
+/// Data reference of the implementation a proxy is upgraded to
+pub fn new_implementation_contract(data: &ProxyUpgradeData) -> Option<&str> {
+    data.implementation
+        .as_deref()
+        .and_then(|i| i.contract.as_deref())
+        .or(data.contract.as_deref())
+}
+
+/// ABI-encode a call on the implementation, or empty calldata when there is none.
+/// `call` is either a full signature or a function name in `contract`'s ABI
+fn encode_call<R: VariableResolver>(
+    call: Option<&str>,
+    contract: Option<&str>,
+    args: &[VariableValue],
+    resolver: &R,
+) -> anyhow::Result<Bytes> {
+    let Some(call) = call else {
+        if !args.is_empty() {
+            anyhow::bail!("Arguments were given without a function to call");
+        }
+        return Ok(Bytes::new());
+    };
+
+    let function = if call.trim_start().starts_with("function") {
+        resolve_function(Some(call), None, None, args.len(), resolver)?
+    } else {
+        resolve_function(None, contract, Some(call), args.len(), resolver)?
+    };
+    let args = args
+        .iter()
+        .zip(&function.inputs)
+        .map(|(arg, param)| {
+            arg.resolve_param(param, resolver)
+                .map_err(|e| anyhow::anyhow!("Failed to resolve {} arg: {:?}", function.name, e))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(Bytes::from(function.abi_encode_input(&args)?))
+}
+
+fn address_value(address: Address) -> VariableValue {
+    VariableValue::Value(format!("0x{:x}", address))
+}
+
+fn bytes_value(bytes: &Bytes) -> VariableValue {
+    VariableValue::Value(format!("0x{}", hex::encode(bytes)))
+}
@@ -0,0 +1,116 @@
+use super::RevertDecoder;
+use alloy::{
+    consensus::TxEnvelope,
+    dyn_abi::DynSolType,
+    eips::eip2718::Decodable2718,
+    primitives::Bytes,
+    providers::{network::TransactionBuilder, Provider},
+    rpc::types::{TransactionReceipt, TransactionRequest},
+};
+use deployer_core::{
+    DeployerError, RawCallData, RawTransactionData, VariableResolver, VariableValue,
+};
+use std::sync::Arc;
+
+pub struct RawExecutor<P> {
+    provider: Arc<P>,
+}
+
+impl<P> RawExecutor<P>
+where
+    P: Provider,
+{
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider }
+    }
+
+    /// Send the calldata as-is after simulating it
+    pub async fn call<R: VariableResolver>(
+        &self,
+        data: &RawCallData,
+        resolver: &R,
+    ) -> anyhow::Result<TransactionReceipt> {
+        let to = data
+            .to
+            .resolve(DynSolType::Address, resolver)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve address: {:?}", e))?
+            .as_address()
+            .ok_or_else(|| anyhow::anyhow!("Failed to resolve address"))?;
+        let value = data
+            .value
+            .resolve(DynSolType::Uint(256), resolver)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve value: {:?}", e))?
+            .as_uint()
+            .map(|(value, _)| value)
+            .ok_or_else(|| anyhow::anyhow!("Failed to resolve value"))?;
+        let input = resolve_bytes(&data.data, resolver)?;
+
+        let tx = TransactionRequest::default()
+            .with_to(to)
+            .with_value(value)
+            .with_input(input);
+
+        let decoder = RevertDecoder::for_action(None, &data.errors, resolver)?;
+        if let Err(e) = self.provider.call(tx.clone()).await {
+            return Err(decoder.transaction_failed("Raw call", e));
+        }
+
+        let receipt = self.provider.send_transaction(tx).await?.get_receipt().await?;
+        check_status("Raw call", &receipt)?;
+
+        println!("{:?}", receipt);
+
+        Ok(receipt)
+    }
+
+    /// Broadcast a signed transaction, or fetch its receipt if it was already mined
+    pub async fn broadcast<R: VariableResolver>(
+        &self,
+        data: &RawTransactionData,
+        resolver: &R,
+    ) -> anyhow::Result<TransactionReceipt> {
+        let raw = resolve_bytes(&data.transaction, resolver)?;
+        let envelope = TxEnvelope::decode_2718(&mut raw.as_ref())
+            .map_err(|e| anyhow::anyhow!("Invalid signed transaction: {}", e))?;
+        let hash = *envelope.tx_hash();
+
+        if let Some(receipt) = self.provider.get_transaction_receipt(hash).await? {
+            println!("Transaction 0x{:x} was already mined", hash);
+            check_status("Raw transaction", &receipt)?;
+            return Ok(receipt);
+        }
+
+        let receipt = self
+            .provider
+            .send_raw_transaction(&raw)
+            .await?
+            .get_receipt()
+            .await?;
+        check_status("Raw transaction", &receipt)?;
+
+        println!("{:?}", receipt);
+
+        Ok(receipt)
+    }
+}
+
+fn resolve_bytes<R: VariableResolver>(value: &VariableValue, resolver: &R) -> anyhow::Result<Bytes> {
+    let bytes = value
+        .resolve(DynSolType::Bytes, resolver)
+        .map_err(|e| anyhow::anyhow!("Failed to resolve bytes: {:?}", e))?;
+    bytes
+        .as_bytes()
+        .map(Bytes::copy_from_slice)
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve bytes"))
+}
+
+fn check_status(context: &str, receipt: &TransactionReceipt) -> anyhow::Result<()> {
+    if !receipt.status() {
+        return Err(DeployerError::TransactionFailed(format!(
+            "{}: transaction 0x{:x} reverted",
+            context, receipt.transaction_hash
+        ))
+        .into());
+    }
+    Ok(())
+}
@@ -1,11 +1,13 @@
 use alloy::{
     contract::CallBuilder,
     dyn_abi::{DynSolType, DynSolValue, JsonAbiExt},
+    eips::BlockId,
     json_abi::Function,
-    primitives::Bytes,
+    primitives::{Address, Bytes},
     providers::Provider,
 };
-use deployer_core::{ReadData, VariableResolver};
+use crate::utils::resolve_function;
+use deployer_core::{resolve_block, ReadData, VariableResolver};
 use std::sync::Arc;
 
 pub struct ReadExecutor<P> {
@@ -25,36 +27,25 @@ where
         data: &ReadData,
         resolver: &R,
     ) -> anyhow::Result<Vec<DynSolValue>> {
-        let function: Function = data.abi_item.parse()?;
-        let address = data
-            .address
-            .resolve(DynSolType::Address, resolver)
-            .map_err(|e| anyhow::anyhow!("Failed to resolve address: {:?}", e))?
-            .as_address()
-            .ok_or_else(|| anyhow::anyhow!("Failed to resolve address"))?;
-
-        let args: Vec<DynSolValue> = data
-            .args
-            .iter()
-            .enumerate()
-            .map(|(i, arg)| {
-                let sol_type = DynSolType::parse(&function.inputs[i].ty)
-                    .map_err(|e| anyhow::anyhow!("Invalid type: {}", e))?;
-                arg.resolve(sol_type, resolver)
-                    .map_err(|e| anyhow::anyhow!("Failed to resolve read arg: {:?}", e))
-            })
-            .collect::<anyhow::Result<Vec<_>>>()?;
-
-        let input = function.abi_encode_input(&args)?;
+        let ReadCall {
+            function,
+            to: address,
+            input,
+        } = build_read_call(data, resolver)?;
+        let block = match &data.block {
+            Some(block) => resolve_block(block, resolver)?,
+            None => BlockId::latest(),
+        };
 
         // Check if contract has bytecode
-        let code = self.provider.get_code_at(address).await?;
+        let code = self.provider.get_code_at(address).block_id(block).await?;
         if code.is_empty() {
-            anyhow::bail!("No contract deployed at address 0x{:x}", address);
+            anyhow::bail!("No contract deployed at address 0x{:x} at block {}", address, block);
         }
 
-        let read_output = CallBuilder::new_raw(self.provider.clone(), Bytes::from(input))
+        let read_output = CallBuilder::new_raw(self.provider.clone(), input)
             .to(address)
+            .block(block)
             .call_raw()
             .with_decoder(&function)
             .await?;
@@ -64,3 +55,46 @@ where
         Ok(read_output)
     }
 }
+
+/// The call a read action makes
+#[derive(Debug, Clone)]
+pub struct ReadCall {
+    pub function: Function,
+    pub to: Address,
+    pub input: Bytes,
+}
+
+/// Resolve a read action's target and calldata
+pub fn build_read_call<R: VariableResolver>(data: &ReadData, resolver: &R) -> anyhow::Result<ReadCall> {
+    let function: Function = resolve_function(
+        data.abi_item.as_deref(),
+        data.contract.as_deref(),
+        data.function.as_deref(),
+        data.args.len(),
+        resolver,
+    )?;
+    let address = data
+        .address
+        .resolve(DynSolType::Address, resolver)
+        .map_err(|e| anyhow::anyhow!("Failed to resolve address: {:?}", e))?
+        .as_address()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve address"))?;
+
+    let args: Vec<DynSolValue> = data
+        .args
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            arg.resolve_param(&function.inputs[i], resolver)
+                .map_err(|e| anyhow::anyhow!("Failed to resolve read arg: {:?}", e))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let input = Bytes::from(function.abi_encode_input(&args)?);
+
+    Ok(ReadCall {
+        function,
+        to: address,
+        input,
+    })
+}
@@ -0,0 +1,94 @@
+use alloy::{
+    dyn_abi::ErrorExt,
+    json_abi::Error,
+    primitives::Bytes,
+    sol_types::{Panic, Revert, SolError},
+    transports::TransportError,
+};
+use deployer_core::{format_sol_value, DeployerError, VariableResolver};
+
+/// Decodes revert data into `Error(string)`, `Panic(uint256)` or one of the
+/// custom errors known for an action
+#[derive(Debug, Default)]
+pub struct RevertDecoder {
+    errors: Vec<Error>,
+}
+
+impl RevertDecoder {
+    pub fn new(errors: Vec<Error>) -> Self {
+        Self { errors }
+    }
+
+    /// Collect custom errors from the action's contract ABI and ad-hoc signatures
+    pub fn for_action<R: VariableResolver>(
+        contract: Option<&str>,
+        signatures: &[String],
+        resolver: &R,
+    ) -> anyhow::Result<Self> {
+        let mut errors = Vec::new();
+
+        if let Some(contract) = contract {
+            let abi = resolver
+                .get_contract_abi(contract)
+                .map_err(|e| anyhow::anyhow!("Failed to load ABI for {}: {}", contract, e))?;
+            errors.extend(abi.errors().cloned());
+        }
+
+        for signature in signatures {
+            let error = Error::parse(signature)
+                .map_err(|e| anyhow::anyhow!("Invalid error signature '{}': {}", signature, e))?;
+            errors.push(error);
+        }
+
+        Ok(Self::new(errors))
+    }
+
+    /// Decode raw revert data into a human readable error
+    pub fn decode(&self, data: &[u8]) -> String {
+        if data.is_empty() {
+            return "reverted without data".to_string();
+        }
+
+        if let Ok(revert) = Revert::abi_decode(data) {
+            return format!("Error({:?})", revert.reason);
+        }
+
+        if let Ok(panic) = Panic::abi_decode(data) {
+            return format!("Panic({})", panic);
+        }
+
+        for error in self.errors.iter().filter(|e| data.starts_with(e.selector().as_slice())) {
+            if let Ok(decoded) = error.decode_error(data) {
+                let args: Vec<String> = error
+                    .inputs
+                    .iter()
+                    .zip(decoded.body.iter())
+                    .map(|(param, value)| {
+                        if param.name.is_empty() {
+                            format_sol_value(value)
+                        } else {
+                            format!("{}: {}", param.name, format_sol_value(value))
+                        }
+                    })
+                    .collect();
+                return format!("{}({})", error.name, args.join(", "));
+            }
+        }
+
+        format!("unknown error 0x{}", hex::encode(data))
+    }
+
+    /// Extract revert data from an RPC error, if the node returned any
+    pub fn revert_data(error: &TransportError) -> Option<Bytes> {
+        error.as_error_resp().and_then(|payload| payload.as_revert_data())
+    }
+
+    /// Convert a failed simulation into a `DeployerError::TransactionFailed`
+    pub fn transaction_failed(&self, context: &str, error: TransportError) -> anyhow::Error {
+        let reason = match Self::revert_data(&error) {
+            Some(data) => self.decode(&data),
+            None => error.to_string(),
+        };
+        DeployerError::TransactionFailed(format!("{}: {}", context, reason)).into()
+    }
+}
@@ -0,0 +1,94 @@
+use super::{ADMIN_SLOT, BEACON_SLOT, IMPLEMENTATION_SLOT};
+use alloy::{
+    dyn_abi::{DynSolType, DynSolValue},
+    eips::BlockId,
+    primitives::B256,
+    providers::Provider,
+};
+use deployer_core::{
+    decode_storage_value, mapping_slot, resolve_block, NamedSlot, StorageReadData,
+    VariableResolver,
+};
+use std::sync::Arc;
+
+pub struct StorageReadExecutor<P> {
+    provider: Arc<P>,
+}
+
+impl<P> StorageReadExecutor<P>
+where
+    P: Provider,
+{
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider }
+    }
+
+    /// Read the slot described by `data` and decode it as `data.ty`
+    pub async fn read_storage<R: VariableResolver>(
+        &self,
+        data: &StorageReadData,
+        resolver: &R,
+    ) -> anyhow::Result<DynSolValue> {
+        let address = data
+            .address
+            .resolve(DynSolType::Address, resolver)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve address: {:?}", e))?
+            .as_address()
+            .ok_or_else(|| anyhow::anyhow!("Failed to resolve address"))?;
+        let slot = storage_slot(data, resolver)?;
+        let ty: DynSolType = data
+            .ty
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid storage type {}: {}", data.ty, e))?;
+        let block = match &data.block {
+            Some(block) => resolve_block(block, resolver)?,
+            None => BlockId::latest(),
+        };
+
+        let word = self
+            .provider
+            .get_storage_at(address, slot.into())
+            .block_id(block)
+            .await?;
+        let value = decode_storage_value(word.into(), &ty, data.offset)?;
+
+        println!("storage 0x{:x} slot 0x{:x}: {:?}", address, slot, value);
+
+        Ok(value)
+    }
+}
+
+/// The slot a storage read targets, after applying any mapping keys
+pub fn storage_slot<R: VariableResolver>(
+    data: &StorageReadData,
+    resolver: &R,
+) -> anyhow::Result<B256> {
+    let mut slot = match (&data.slot, data.named_slot) {
+        (Some(slot), None) => match slot
+            .resolve(DynSolType::Uint(256), resolver)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve slot: {:?}", e))?
+        {
+            DynSolValue::Uint(slot, _) => B256::from(slot),
+            DynSolValue::FixedBytes(slot, 32) => slot,
+            other => anyhow::bail!("Slot must be a number or bytes32, got {:?}", other),
+        },
+        (None, Some(NamedSlot::Implementation)) => IMPLEMENTATION_SLOT,
+        (None, Some(NamedSlot::Admin)) => ADMIN_SLOT,
+        (None, Some(NamedSlot::Beacon)) => BEACON_SLOT,
+        _ => anyhow::bail!("Storage read requires exactly one of slot or named_slot"),
+    };
+
+    for key in &data.keys {
+        let ty: DynSolType = key
+            .ty
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid key type {}: {}", key.ty, e))?;
+        let value = key
+            .value
+            .resolve(ty, resolver)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve mapping key: {:?}", e))?;
+        slot = mapping_slot(slot, &value);
+    }
+
+    Ok(slot)
+}
@@ -0,0 +1,92 @@
+use super::RevertDecoder;
+use alloy::{
+    dyn_abi::DynSolType,
+    primitives::{Bytes, U256},
+    providers::{network::TransactionBuilder, Provider},
+    rpc::types::{TransactionReceipt, TransactionRequest},
+};
+use deployer_core::{DeployerError, TransferData, VariableResolver};
+use std::sync::Arc;
+
+pub struct TransferExecutor<P> {
+    provider: Arc<P>,
+}
+
+impl<P> TransferExecutor<P>
+where
+    P: Provider,
+{
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider }
+    }
+
+    /// Send the transfer, returning the amount sent and, unless nothing needed
+    /// to be sent, the receipt
+    pub async fn transfer<R: VariableResolver>(
+        &self,
+        data: &TransferData,
+        resolver: &R,
+    ) -> anyhow::Result<(U256, Option<TransactionReceipt>)> {
+        let to = data
+            .to
+            .resolve(DynSolType::Address, resolver)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve recipient: {:?}", e))?
+            .as_address()
+            .ok_or_else(|| anyhow::anyhow!("Failed to resolve recipient"))?;
+        let amount = data
+            .amount
+            .resolve(DynSolType::Uint(256), resolver)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve amount: {:?}", e))?
+            .as_uint()
+            .map(|(amount, _)| amount)
+            .ok_or_else(|| anyhow::anyhow!("Failed to resolve amount"))?;
+        let input = match &data.data {
+            Some(input) => Bytes::from(
+                input
+                    .resolve(DynSolType::Bytes, resolver)
+                    .map_err(|e| anyhow::anyhow!("Failed to resolve data: {:?}", e))?
+                    .as_bytes()
+                    .ok_or_else(|| anyhow::anyhow!("Failed to resolve data"))?
+                    .to_vec(),
+            ),
+            None => Bytes::new(),
+        };
+
+        let value = if data.top_up {
+            let balance = self.provider.get_balance(to).await?;
+            if balance >= amount {
+                println!(
+                    "0x{:x} already holds {} wei, at least {} wei",
+                    to, balance, amount
+                );
+                return Ok((U256::ZERO, None));
+            }
+            amount - balance
+        } else {
+            amount
+        };
+
+        let tx = TransactionRequest::default()
+            .with_to(to)
+            .with_value(value)
+            .with_input(input);
+
+        // Simulate first so a recipient that rejects the value is caught early
+        if let Err(e) = self.provider.call(tx.clone()).await {
+            return Err(RevertDecoder::default().transaction_failed("Transfer", e));
+        }
+
+        let receipt = self.provider.send_transaction(tx).await?.get_receipt().await?;
+        if !receipt.status() {
+            return Err(DeployerError::TransactionFailed(format!(
+                "Transfer: transaction 0x{:x} reverted",
+                receipt.transaction_hash
+            ))
+            .into());
+        }
+
+        println!("Sent {} wei to 0x{:x}", value, to);
+
+        Ok((value, Some(receipt)))
+    }
+}
@@ -1,11 +1,13 @@
 use alloy::{
     dyn_abi::{DynSolType, DynSolValue, JsonAbiExt},
-    json_abi::Function,
-    primitives::Bytes,
+    json_abi::{Function, StateMutability},
+    primitives::{Address, Bytes, U256},
     providers::{network::TransactionBuilder, Provider},
-    rpc::types::TransactionRequest,
+    rpc::types::{TransactionReceipt, TransactionRequest},
 };
-use deployer_core::{VariableResolver, WriteData};
+use super::RevertDecoder;
+use crate::utils::resolve_function;
+use deployer_core::{DeployerError, VariableResolver, WriteData};
 use std::sync::Arc;
 
 pub struct WriteExecutor<P> {
@@ -24,7 +26,7 @@ where
         &self,
         data: &WriteData,
         resolver: &R,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<TransactionReceipt> {
         // // Check condition if present
         // if let Some(condition) = &data.condition {
         //     let should_execute = condition.evaluate(&self.provider, resolver).await
@@ -37,45 +39,127 @@ where
 
         //     println!("Condition met, executing write action");
         // }
-        let function: Function = data.abi_item.parse()?;
-        let address = data
-            .address
-            .resolve(DynSolType::Address, resolver)
-            .map_err(|e| anyhow::anyhow!("Failed to resolve address: {:?}", e))?
-            .as_address()
-            .ok_or_else(|| anyhow::anyhow!("Failed to resolve address"))?;
-
-        let value = data
-            .value
-            .resolve(DynSolType::Uint(256), resolver)
-            .map_err(|e| anyhow::anyhow!("Failed to resolve value: {:?}", e))?
-            .as_uint()
-            .ok_or_else(|| anyhow::anyhow!("Failed to resolve value as uint256"))?;
-
-        let args: Vec<DynSolValue> = data
-            .args
-            .iter()
-            .enumerate()
-            .map(|(i, arg)| {
-                let sol_type = DynSolType::parse(&function.inputs[i].ty)
-                    .map_err(|e| anyhow::anyhow!("Invalid type: {}", e))?;
-                arg.resolve(sol_type, resolver)
-                    .map_err(|e| anyhow::anyhow!("Failed to resolve write arg: {:?}", e))
-            })
-            .collect::<anyhow::Result<Vec<_>>>()?;
-
-        let input = function.abi_encode_input(&args)?;
+        let WriteCall {
+            function,
+            to,
+            value,
+            input,
+        } = build_call(data, resolver)?;
 
         let tx = TransactionRequest::default()
-            .with_to(address)
-            .with_value(value.0)
-            .with_input(Bytes::from(input));
+            .with_to(to)
+            .with_value(value)
+            .with_input(input);
+
+        // Simulate first so reverts can be decoded before anything is sent
+        let decoder =
+            RevertDecoder::for_action(data.contract.as_deref(), &data.errors, resolver)?;
+        if let Err(e) = self.provider.call(tx.clone()).await {
+            return Err(decoder.transaction_failed(&function.name, e));
+        }
 
         let pending_tx = self.provider.send_transaction(tx).await?;
         let receipt = pending_tx.get_receipt().await?;
 
+        if !receipt.status() {
+            return Err(DeployerError::TransactionFailed(format!(
+                "{}: transaction 0x{:x} reverted",
+                function.name, receipt.transaction_hash
+            ))
+            .into());
+        }
+
         println!("{:?}", receipt);
 
-        Ok(())
+        Ok(receipt)
+    }
+}
+
+/// The call a write action makes, before it's sent from any particular account
+#[derive(Debug, Clone)]
+pub struct WriteCall {
+    pub function: Function,
+    pub to: Address,
+    pub value: U256,
+    pub input: Bytes,
+}
+
+/// Resolve a write action's target, value and calldata
+pub fn build_call<R: VariableResolver>(data: &WriteData, resolver: &R) -> anyhow::Result<WriteCall> {
+    let function: Function = resolve_function(
+        data.abi_item.as_deref(),
+        data.contract.as_deref(),
+        data.function.as_deref(),
+        data.args.len(),
+        resolver,
+    )?;
+    let address = data
+        .address
+        .resolve(DynSolType::Address, resolver)
+        .map_err(|e| anyhow::anyhow!("Failed to resolve address: {:?}", e))?
+        .as_address()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve address"))?;
+
+    let value = data
+        .value
+        .resolve(DynSolType::Uint(256), resolver)
+        .map_err(|e| anyhow::anyhow!("Failed to resolve value: {:?}", e))?
+        .as_uint()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve value as uint256"))?;
+
+    check_payable(&function, value.0)?;
+
+    let args: Vec<DynSolValue> = data
+        .args
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            arg.resolve_param(&function.inputs[i], resolver)
+                .map_err(|e| anyhow::anyhow!("Failed to resolve write arg: {:?}", e))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let input = Bytes::from(function.abi_encode_input(&args)?);
+
+    Ok(WriteCall {
+        function,
+        to: address,
+        value: value.0,
+        input,
+    })
+}
+
+/// Check a write action before the run starts: its function must resolve and
+/// can't be sent value unless it's payable. Values that depend on outputs of
+/// earlier actions are checked once the call is built
+pub fn validate_write<R: VariableResolver>(data: &WriteData, resolver: &R) -> anyhow::Result<()> {
+    let function = resolve_function(
+        data.abi_item.as_deref(),
+        data.contract.as_deref(),
+        data.function.as_deref(),
+        data.args.len(),
+        resolver,
+    )?;
+    if let Some((value, _)) = data
+        .value
+        .resolve(DynSolType::Uint(256), resolver)
+        .ok()
+        .and_then(|value| value.as_uint())
+    {
+        check_payable(&function, value)?;
+    }
+    Ok(())
+}
+
+/// Reject value for functions not marked payable, whether declared by a
+/// contract ABI or an `abi_item`
+fn check_payable(function: &Function, value: U256) -> anyhow::Result<()> {
+    if !value.is_zero() && function.state_mutability != StateMutability::Payable {
+        anyhow::bail!(
+            "Function {} is not payable but value {} was provided",
+            function.signature(),
+            value
+        );
     }
+    Ok(())
 }
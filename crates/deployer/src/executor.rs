@@ -1,11 +1,26 @@
 use crate::{
     config::config::Config,
     data_indexer::DataIndexer,
-    execution::{DeploymentExecutor, ReadExecutor, WriteExecutor},
-    utils::topological_sort,
+    execution::{
+        build_call, build_initcode, new_implementation_contract, proxy_deployment_data, validate_batch, validate_write,
+        DeploymentExecutor, MulticallExecutor, ProxyExecutor, RawExecutor, ReadExecutor, StorageReadExecutor, TransferExecutor, WriteCall, WriteExecutor, ADMIN_SLOT,
+    },
+    safe::{execute_safe_transaction, safe_nonce, SafeTransaction, SafeTransactionService, TxBuilderBatch, MULTI_SEND_CALL_ONLY},
+    utils::{resolve_function, topological_levels},
+    verification::{VerificationRequest, Verifier},
+};
+use alloy::{
+    dyn_abi::FunctionExt,
+    primitives::{Address, U256},
+    providers::{network::Ethereum, Provider},
+    rpc::types::TransactionReceipt,
+    signers::{local::PrivateKeySigner, Signer},
+};
+use deployer_core::{
+    check_storage_upgrade, format_sol_value, Action, ActionData, AssertData, BatchData, ChainReadData, Condition, DeployerError, DeploymentData, ProxyDeploymentData, ProxyKind, ProxyUpgradeData, RawCallData, RawTransactionData, ReadBatchConfig, ReadData,
+    RunOutput, SafeMode, StorageReadData, TransferData, SafeTransactionResult, Secret, SecretStore, Sender, StorageLayout, VariableResolver, VariableValue, VerificationConfig,
+    VerificationResult, VerificationStatus, WriteData,
 };
-use alloy::providers::{network::Ethereum, Provider};
-use deployer_core::{ActionData, DeploymentData, ReadData, WriteData};
 use std::{path::PathBuf, sync::Arc};
 
 #[derive(Debug)]
@@ -14,6 +29,13 @@ pub struct Executor<P> {
     indexer: Option<DataIndexer>,
     config: Config,
     data_dir: PathBuf,
+    secrets: SecretStore,
+    /// Key of the account sending transactions, used to sign Safe proposals
+    signer: Option<PrivateKeySigner>,
+    /// Calls from `sender: safe` writes, submitted as one Safe transaction after
+    /// the run or before the first action that depends on one of them
+    safe_calls: Vec<(String, WriteCall)>,
+    output: RunOutput,
 }
 
 impl<P> Executor<P>
@@ -30,9 +52,30 @@ where
             indexer: None,
             config: Config::new(),
             data_dir,
+            secrets: SecretStore::default(),
+            signer: None,
+            safe_calls: Vec::new(),
+            output: RunOutput::default(),
         }
     }
 
+    /// Resolve `!secret` values from `secrets`
+    pub fn with_secrets(mut self, secrets: SecretStore) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// Sign Safe proposals with `signer`, which should be the provider's wallet key
+    pub fn with_signer(mut self, signer: PrivateKeySigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Results collected by the last run, such as verification outcomes
+    pub fn run_output(&self) -> &RunOutput {
+        &self.output
+    }
+
     pub async fn execute_actions(&mut self) -> anyhow::Result<()> {
         // Ensure indexer is initialized
         if self.indexer.is_none() {
@@ -40,15 +83,87 @@ where
         }
         
         let actions = self.config.actions.clone();
-        let sorted = topological_sort(actions)?;
-        for action in sorted {
-            match &action.action_data {
-                ActionData::Deploy(deploy_data) => {
-                    self.deploy(action.id.clone(), deploy_data).await?
+        for level in topological_levels(actions)? {
+            self.flush_safe_calls_for(&level).await?;
+
+            // Reads whose dependencies are all met go out together first
+            let level = match self.config.read_batch.clone() {
+                Some(read_batch) => {
+                    let (reads, rest): (Vec<_>, Vec<_>) = level.into_iter().partition(|action| {
+                        matches!(&action.action_data, ActionData::Read(read) if read.block.is_none())
+                    });
+                    self.read_batch(&reads, &read_batch).await?;
+                    rest
                 }
-                ActionData::Write(write_data) => self.write(write_data).await?,
-                ActionData::Read(read_data) => self.read(action.id, read_data).await?,
+                None => level,
+            };
+            for action in level {
+                self.execute_action(action).await?;
+            }
+        }
+
+        if !self.safe_calls.is_empty() {
+            self.submit_safe_transaction().await?;
+        }
+        Ok(())
+    }
+
+    /// Submit the queued Safe calls if an action in `level` depends on one of
+    /// them, since it would otherwise run against the state before the write.
+    /// Only `execute` mode puts the writes on chain mid-run, so other modes
+    /// reject such pipelines
+    async fn flush_safe_calls_for(&mut self, level: &[Action]) -> anyhow::Result<()> {
+        let dependent = level
+            .iter()
+            // Safe writes can follow the calls they depend on in the same transaction
+            .filter(|action| !matches!(&action.action_data, ActionData::Write(data) if self.sends_from_safe(data)))
+            .find_map(|action| {
+                action
+                    .depends_on
+                    .iter()
+                    .flatten()
+                    .find(|dependency| self.safe_calls.iter().any(|(id, _)| id == *dependency))
+                    .map(|dependency| (action.id.clone(), dependency.clone()))
+            });
+        let Some((action_id, dependency)) = dependent else {
+            return Ok(());
+        };
+
+        let mode = self.config.safe.as_ref().map(|safe| safe.mode).unwrap_or_default();
+        if mode != SafeMode::Execute {
+            anyhow::bail!(
+                "{} depends on {}, which is queued for a Safe transaction that only runs after the pipeline; use safe mode execute or split the pipeline",
+                action_id,
+                dependency
+            );
+        }
+        self.submit_safe_transaction().await
+    }
+
+    async fn execute_action(&mut self, action: Action) -> anyhow::Result<()> {
+        match &action.action_data {
+            ActionData::Deploy(deploy_data) => {
+                self.deploy(action.id.clone(), deploy_data).await?;
+            }
+            ActionData::Write(write_data) => self.write(action.id, write_data).await?,
+            ActionData::Read(read_data) => self.read(action.id, read_data).await?,
+            ActionData::DeployProxy(proxy_data) => {
+                self.deploy_proxy(action.id, proxy_data).await?
+            }
+            ActionData::UpgradeProxy(upgrade_data) => {
+                self.upgrade_proxy(action.id, upgrade_data).await?
+            }
+            ActionData::Batch(batch_data) => self.batch(action.id, batch_data).await?,
+            ActionData::ReadStorage(storage_data) => {
+                self.read_storage(action.id, storage_data).await?
             }
+            ActionData::ReadChain(chain_data) => self.read_chain(action.id, chain_data).await?,
+            ActionData::Transfer(transfer_data) => self.transfer(action.id, transfer_data).await?,
+            ActionData::RawCall(call_data) => self.raw_call(action.id, call_data).await?,
+            ActionData::RawTransaction(transaction_data) => {
+                self.raw_transaction(action.id, transaction_data).await?
+            }
+            ActionData::Assert(assert_data) => self.assert(action.id, assert_data).await?,
         }
         Ok(())
     }
@@ -60,27 +175,371 @@ where
         let read_executor = ReadExecutor::new(self.provider.clone());
         let read_output = read_executor.read(data, indexer).await?;
 
-        let function: alloy::json_abi::Function = data.abi_item.parse()?;
+        let function = resolve_function(
+            data.abi_item.as_deref(),
+            data.contract.as_deref(),
+            data.function.as_deref(),
+            data.args.len(),
+            indexer,
+        )?;
         self.indexer.as_mut().unwrap()
             .save_output_data(id, function.outputs.clone(), read_output)?;
 
         Ok(())
     }
 
-    async fn write(&self, data: &WriteData) -> anyhow::Result<()> {
+    async fn read_storage(&mut self, id: String, data: &StorageReadData) -> anyhow::Result<()> {
         let indexer = self.indexer.as_ref()
             .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
-        
+
+        let storage_executor = StorageReadExecutor::new(self.provider.clone());
+        let value = storage_executor.read_storage(data, indexer).await?;
+
+        let output = alloy::json_abi::Param {
+            name: "".to_string(),
+            ty: data.ty.clone(),
+            internal_type: None,
+            components: vec![],
+        };
+        self.indexer.as_mut().unwrap()
+            .save_output_data(id, vec![output], vec![value])?;
+
+        Ok(())
+    }
+
+    async fn read_chain(&mut self, id: String, data: &ChainReadData) -> anyhow::Result<()> {
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+
+        let value = data.read(self.provider.as_ref(), indexer).await?;
+        println!("{}: {} = {}", id, data.query.name(), format_sol_value(&value));
+
+        let output = alloy::json_abi::Param {
+            name: "".to_string(),
+            ty: data.query.sol_type().to_string(),
+            internal_type: None,
+            components: vec![],
+        };
+        self.indexer.as_mut().unwrap()
+            .save_output_data(id, vec![output], vec![value])?;
+
+        Ok(())
+    }
+
+    /// Make read actions through Multicall3 `aggregate3`, indexing outputs
+    /// under the same keys as individual reads
+    async fn read_batch(&mut self, actions: &[Action], config: &ReadBatchConfig) -> anyhow::Result<()> {
+        let reads: Vec<(String, &ReadData)> = actions
+            .iter()
+            .filter_map(|action| match &action.action_data {
+                ActionData::Read(data) => Some((action.id.clone(), data)),
+                _ => None,
+            })
+            .collect();
+        if reads.is_empty() {
+            return Ok(());
+        }
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+
+        let multicall_executor = MulticallExecutor::new(self.provider.clone());
+        let results = multicall_executor.read_batch(&reads, config, indexer).await?;
+
+        for (id, function, outputs) in results {
+            self.indexer.as_mut().unwrap()
+                .save_output_data(id, function.outputs, outputs)?;
+        }
+        Ok(())
+    }
+
+    /// Send a batch of writes through Multicall3, indexing the transaction under
+    /// the batch's id and each call's return values under its own id
+    async fn batch(&mut self, action_id: String, data: &BatchData) -> anyhow::Result<()> {
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+
+        let multicall_executor = MulticallExecutor::new(self.provider.clone());
+        let (receipt, results) = multicall_executor.write_batch(data, indexer).await?;
+        self.save_receipt(action_id, &receipt)?;
+
+        for result in results.into_iter().filter(|result| result.success) {
+            if result.function.outputs.is_empty() {
+                continue;
+            }
+            let outputs = result.function.abi_decode_output(&result.return_data)
+                .map_err(|e| anyhow::anyhow!("Failed to decode output of batch call {}: {}", result.id, e))?;
+            self.indexer.as_mut().unwrap()
+                .save_output_data(result.id, result.function.outputs.clone(), outputs)?;
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, action_id: String, data: &WriteData) -> anyhow::Result<()> {
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+
+        if self.sends_from_safe(data) {
+            if self.config.safe.is_none() {
+                anyhow::bail!("Write {} is sent from the Safe but no safe is configured", action_id);
+            }
+            // Sent with the other queued calls once the run ends or an action depends on one
+            let call = build_call(data, indexer)?;
+            println!("Queued {} for the Safe transaction", call.function.name);
+            self.safe_calls.push((action_id, call));
+            return Ok(());
+        }
+
         let write_executor = WriteExecutor::new(self.provider.clone());
-        write_executor.write(data, indexer).await
+        let receipt = write_executor.write(data, indexer).await?;
+        self.save_receipt(action_id, &receipt)
+    }
+
+    fn sends_from_safe(&self, data: &WriteData) -> bool {
+        let default_sender = self.config.safe.as_ref().map(|safe| safe.sender);
+        data.sender.or(default_sender).unwrap_or_default() == Sender::Safe
+    }
+
+    async fn transfer(&mut self, action_id: String, data: &TransferData) -> anyhow::Result<()> {
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+
+        let transfer_executor = TransferExecutor::new(self.provider.clone());
+        let (amount, receipt) = transfer_executor.transfer(data, indexer).await?;
+
+        let amount_output = alloy::json_abi::Param {
+            name: "amount".to_string(),
+            ty: "uint256".to_string(),
+            internal_type: None,
+            components: vec![],
+        };
+        self.indexer.as_mut().unwrap().save_output_data(
+            action_id.clone(),
+            vec![amount_output],
+            vec![alloy::dyn_abi::DynSolValue::Uint(amount, 256)],
+        )?;
+        if let Some(receipt) = &receipt {
+            self.save_receipt(action_id, receipt)?;
+        }
+        Ok(())
+    }
+
+    async fn raw_call(&mut self, action_id: String, data: &RawCallData) -> anyhow::Result<()> {
+        if !self.condition_met(&action_id, data.condition.as_ref()).await? {
+            return Ok(());
+        }
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+
+        let raw_executor = RawExecutor::new(self.provider.clone());
+        let receipt = raw_executor.call(data, indexer).await?;
+        self.save_receipt(action_id, &receipt)
+    }
+
+    /// Broadcast a signed transaction, also indexing `<id>.contract_address`
+    /// when it creates a contract
+    async fn raw_transaction(&mut self, action_id: String, data: &RawTransactionData) -> anyhow::Result<()> {
+        if !self.condition_met(&action_id, data.condition.as_ref()).await? {
+            return Ok(());
+        }
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+
+        let raw_executor = RawExecutor::new(self.provider.clone());
+        let receipt = raw_executor.broadcast(data, indexer).await?;
+        if let Some(contract_address) = receipt.contract_address {
+            let output = alloy::json_abi::Param {
+                name: "contract_address".to_string(),
+                ty: "address".to_string(),
+                internal_type: None,
+                components: vec![],
+            };
+            self.indexer.as_mut().unwrap().save_output_data(
+                action_id.clone(),
+                vec![output],
+                vec![alloy::dyn_abi::DynSolValue::Address(contract_address)],
+            )?;
+        }
+        self.save_receipt(action_id, &receipt)
+    }
+
+    async fn assert(&self, action_id: String, data: &AssertData) -> anyhow::Result<()> {
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+
+        let comparison = data.condition.comparison();
+        let current_value = data.condition.current_value(self.provider.as_ref(), indexer).await?;
+        if comparison.evaluate(&current_value, indexer)? {
+            println!("Assertion {} passed", action_id);
+            return Ok(());
+        }
+
+        let expected = comparison.describe(&current_value, indexer)?;
+        Err(DeployerError::AssertionFailed(format!(
+            "{}{}: got {}, expected {}",
+            action_id,
+            data.message.as_ref().map(|m| format!(" ({})", m)).unwrap_or_default(),
+            format_sol_value(&current_value),
+            expected
+        ))
+        .into())
+    }
+
+    /// Whether an action with an optional condition should run
+    async fn condition_met(&self, action_id: &str, condition: Option<&Condition>) -> anyhow::Result<bool> {
+        let Some(condition) = condition else {
+            return Ok(true);
+        };
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+
+        let met = condition.evaluate(self.provider.as_ref(), indexer).await
+            .map_err(|e| anyhow::anyhow!("Failed to evaluate condition of {}: {:?}", action_id, e))?;
+        if !met {
+            println!("Condition of {} not met, skipping", action_id);
+        }
+        Ok(met)
+    }
+
+    /// Index `<id>.transaction_hash` and `<id>.block_number` of a sent transaction
+    fn save_receipt(&mut self, action_id: String, receipt: &TransactionReceipt) -> anyhow::Result<()> {
+        let param = |name: &str, ty: &str| alloy::json_abi::Param {
+            name: name.to_string(),
+            ty: ty.to_string(),
+            internal_type: None,
+            components: vec![],
+        };
+        let block_number = receipt.block_number
+            .ok_or_else(|| anyhow::anyhow!("Receipt of {} has no block number", action_id))?;
+        self.indexer.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?
+            .save_output_data(
+                action_id,
+                vec![param("transaction_hash", "bytes32"), param("block_number", "uint256")],
+                vec![
+                    alloy::dyn_abi::DynSolValue::FixedBytes(receipt.transaction_hash, 32),
+                    alloy::dyn_abi::DynSolValue::Uint(U256::from(block_number), 256),
+                ],
+            )
+    }
+
+    /// Turn the queued `sender: safe` calls into one Safe transaction and write
+    /// it as a Transaction Builder file or propose it to the transaction service
+    async fn submit_safe_transaction(&mut self) -> anyhow::Result<()> {
+        let safe_config = self.config.safe.clone()
+            .ok_or_else(|| anyhow::anyhow!("No safe configured"))?;
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+        let safe = resolve_address(&safe_config.address, indexer)?;
+        let multisend = match &safe_config.multisend {
+            Some(multisend) => resolve_address(multisend, indexer)?,
+            None => MULTI_SEND_CALL_ONLY,
+        };
+
+        let queued = std::mem::take(&mut self.safe_calls);
+        let (actions, calls): (Vec<String>, Vec<&WriteCall>) =
+            queued.iter().map(|(id, call)| (id.clone(), call)).unzip();
+        let nonce = match safe_config.nonce {
+            // Earlier Safe transactions in this run used the nonces before it
+            Some(nonce) => nonce + self.output.safe_transactions.len() as u64,
+            None => safe_nonce(self.provider.clone(), safe).await?,
+        };
+        let chain_id = self.provider.get_chain_id().await?;
+        let transaction = SafeTransaction::from_calls(&calls, multisend, nonce);
+        let safe_tx_hash = transaction.hash(safe, chain_id);
+        let name = safe_config.name.as_deref().unwrap_or("deployer-of-worlds");
+
+        let destination = match safe_config.mode {
+            SafeMode::TxBuilder => {
+                TxBuilderBatch::new(chain_id, safe, name, &calls)
+                    .write(std::path::Path::new(&safe_config.output))?;
+                safe_config.output.clone()
+            }
+            SafeMode::Propose => {
+                let url = safe_config.service_url.as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("Safe propose mode requires service_url"))?;
+                let signer = self.signer.as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("Safe propose mode requires a signer"))?;
+                let signature = signer.sign_hash(&safe_tx_hash).await?;
+                SafeTransactionService::new(url)
+                    .propose(
+                        safe,
+                        &transaction,
+                        safe_tx_hash,
+                        signer.address(),
+                        &signature.as_bytes(),
+                        name,
+                    )
+                    .await?;
+                url.to_string()
+            }
+            SafeMode::Execute => {
+                let owners = safe_config
+                    .owners
+                    .iter()
+                    .map(|owner| {
+                        let key = owner
+                            .resolve(alloy::dyn_abi::DynSolType::FixedBytes(32), indexer)
+                            .map_err(|e| anyhow::anyhow!("Failed to resolve Safe owner key: {}", e))?;
+                        let key = key.as_fixed_bytes()
+                            .ok_or_else(|| anyhow::anyhow!("Safe owner key must be 32 bytes"))?
+                            .0;
+                        Ok(PrivateKeySigner::from_slice(key)?)
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let receipt = execute_safe_transaction(
+                    self.provider.clone(),
+                    safe,
+                    &transaction,
+                    safe_tx_hash,
+                    &owners,
+                )
+                .await?;
+                println!("{:?}", receipt);
+
+                // Index the outcome under each action that was part of the transaction
+                for action_id in &actions {
+                    let safe_tx_hash_param = alloy::json_abi::Param {
+                        name: "safe_tx_hash".to_string(),
+                        ty: "bytes32".to_string(),
+                        internal_type: None,
+                        components: vec![],
+                    };
+                    self.indexer.as_mut().unwrap().save_output_data(
+                        action_id.clone(),
+                        vec![safe_tx_hash_param],
+                        vec![alloy::dyn_abi::DynSolValue::FixedBytes(safe_tx_hash, 32)],
+                    )?;
+                    self.save_receipt(action_id.clone(), &receipt)?;
+                }
+                format!("0x{:x}", receipt.transaction_hash)
+            }
+        };
+
+        println!(
+            "Safe transaction 0x{:x} for 0x{:x} (nonce {}, {} calls) sent to {}",
+            safe_tx_hash,
+            safe,
+            nonce,
+            actions.len(),
+            destination
+        );
+        self.output.safe_transactions.push(SafeTransactionResult {
+            safe,
+            safe_tx_hash,
+            nonce,
+            actions,
+            mode: safe_config.mode,
+            destination,
+        });
+        Ok(())
     }
 
-    async fn deploy(&mut self, action_id: String, data: &DeploymentData) -> anyhow::Result<()> {
+    async fn deploy(&mut self, action_id: String, data: &DeploymentData) -> anyhow::Result<Address> {
         let indexer = self.indexer.as_ref()
             .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
         
         let deployment_executor = DeploymentExecutor::new(self.provider.clone());
-        let (deployed_address, _tx_hash) = deployment_executor.deploy(data, indexer).await?;
+        let (deployed_address, receipt) = deployment_executor.deploy(data, indexer).await?;
 
         // Save deployed address to indexer so it can be referenced by !output
         // For deployment outputs, we store directly with the action ID as the key
@@ -96,25 +555,255 @@ where
             vec![address_output],
             vec![alloy::dyn_abi::DynSolValue::Address(deployed_address)],
         )?;
+        if let Some(receipt) = &receipt {
+            self.save_receipt(action_id.clone(), receipt)?;
+        }
+
+        if let Some(verification) = self.config.verification.clone().filter(|v| v.enabled) {
+            // Only fresh deployments are submitted; an existing contract was
+            // verified (or not) by whichever run deployed it
+            let result = match receipt {
+                Some(_) => self.verify(action_id, data, deployed_address, verification).await,
+                None => VerificationResult {
+                    action_id,
+                    address: deployed_address,
+                    provider: verification.provider,
+                    status: VerificationStatus::Skipped,
+                    message: "Contract was already deployed".to_string(),
+                },
+            };
+            self.output.verifications.push(result);
+        }
+
+        Ok(deployed_address)
+    }
+
+    /// Deploy the implementation (if given) and a proxy pointing at it, indexing
+    /// `<id>` as the proxy and `<id>.implementation` as the implementation
+    async fn deploy_proxy(&mut self, action_id: String, data: &ProxyDeploymentData) -> anyhow::Result<()> {
+        let implementation = self
+            .implementation(&action_id, data.implementation.as_deref(), data.implementation_address.as_ref())
+            .await?;
+
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+        let proxy_data = proxy_deployment_data(data, implementation, indexer)?;
+        let proxy = self.deploy(action_id.clone(), &proxy_data).await?;
 
+        // Read back from the proxy so beacon proxies index the beacon's implementation
+        let proxy_executor = ProxyExecutor::new(self.provider.clone());
+        let mut addresses = vec![(
+            "implementation",
+            proxy_executor.current_implementation(data.kind, proxy).await?,
+        )];
+        if data.kind == ProxyKind::Transparent {
+            addresses.push(("admin", proxy_executor.read_slot_address(proxy, ADMIN_SLOT).await?));
+        }
+        println!("Proxy 0x{:x} uses implementation 0x{:x}", proxy, addresses[0].1);
+        self.save_addresses(action_id, &addresses)
+    }
+
+    /// Upgrade a proxy to a new implementation unless it already uses it, indexing
+    /// `<id>` as the proxy and `<id>.implementation` / `<id>.previous_implementation`
+    async fn upgrade_proxy(&mut self, action_id: String, data: &ProxyUpgradeData) -> anyhow::Result<()> {
+        self.check_storage_layout(data)?;
+
+        let implementation = self
+            .implementation(&action_id, data.implementation.as_deref(), data.implementation_address.as_ref())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Upgrade requires implementation or implementation_address"))?;
+
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+        let proxy = resolve_address(&data.proxy, indexer)?;
+
+        let proxy_executor = ProxyExecutor::new(self.provider.clone());
+        let current = proxy_executor.current_implementation(data.kind, proxy).await?;
+        if current == implementation {
+            println!("Proxy 0x{:x} already uses implementation 0x{:x}, skipping upgrade", proxy, implementation);
+        } else {
+            let write_data = proxy_executor.upgrade_write(data, proxy, implementation, indexer).await?;
+            println!("Upgrading proxy 0x{:x} from 0x{:x} to 0x{:x}", proxy, current, implementation);
+            self.write(action_id.clone(), &write_data).await?;
+        }
+
+        self.save_addresses(
+            action_id,
+            &[("", proxy), ("implementation", implementation), ("previous_implementation", current)],
+        )
+    }
+
+    /// Block an upgrade whose new implementation would corrupt the proxy's storage
+    fn check_storage_layout(&self, data: &ProxyUpgradeData) -> anyhow::Result<()> {
+        if data.unsafe_skip_storage_check {
+            println!("Skipping storage layout check");
+            return Ok(());
+        }
+        let previous = data.previous_contract.as_deref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Upgrade requires previous_contract to check the storage layout, \
+                 or unsafe_skip_storage_check to upgrade without it"
+            )
+        })?;
+        let contract = new_implementation_contract(data).ok_or_else(|| {
+            anyhow::anyhow!("Storage layout check requires the contract of the new implementation")
+        })?;
+
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+        let old_layout = StorageLayout::from_contract(&indexer.get_contract(previous)?)?;
+        let new_layout = StorageLayout::from_contract(&indexer.get_contract(contract)?)?;
+
+        let report = check_storage_upgrade(&old_layout, &new_layout);
+        if !report.is_compatible() {
+            return Err(DeployerError::StorageLayout(format!(
+                "{} cannot replace {}:\n{}",
+                contract, previous, report
+            ))
+            .into());
+        }
+        println!("Storage layout of {} is compatible with {}", contract, previous);
         Ok(())
     }
 
+    /// Deploy a proxy's implementation under `<id>.implementation`, or resolve an existing one
+    async fn implementation(
+        &mut self,
+        action_id: &str,
+        deployment: Option<&DeploymentData>,
+        address: Option<&VariableValue>,
+    ) -> anyhow::Result<Option<Address>> {
+        match (deployment, address) {
+            (Some(_), Some(_)) => {
+                anyhow::bail!("Specify either implementation or implementation_address, not both")
+            }
+            (Some(deployment), None) => Ok(Some(
+                self.deploy(format!("{}.implementation", action_id), deployment).await?,
+            )),
+            (None, Some(address)) => {
+                let indexer = self.indexer.as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+                resolve_address(address, indexer).map(Some)
+            }
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn save_addresses(&mut self, action_id: String, addresses: &[(&str, Address)]) -> anyhow::Result<()> {
+        let (params, values) = addresses
+            .iter()
+            .map(|(name, address)| {
+                let param = alloy::json_abi::Param {
+                    name: name.to_string(),
+                    ty: "address".to_string(),
+                    internal_type: None,
+                    components: vec![],
+                };
+                (param, alloy::dyn_abi::DynSolValue::Address(*address))
+            })
+            .unzip();
+        self.indexer.as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?
+            .save_output_data(action_id, params, values)
+    }
+
+    /// Verify a deployed contract; failures are recorded rather than aborting the run
+    async fn verify(
+        &self,
+        action_id: String,
+        data: &DeploymentData,
+        address: Address,
+        config: VerificationConfig,
+    ) -> VerificationResult {
+        let provider = config.provider;
+        let (status, message) = match self.verification_request(data, address).await {
+            Ok(request) => match self.verifier(config) {
+                Ok(verifier) => verifier
+                    .verify(&request)
+                    .await
+                    .unwrap_or_else(|e| (VerificationStatus::Failed, e.to_string())),
+                Err(e) => (VerificationStatus::Failed, e.to_string()),
+            },
+            Err(e) => (VerificationStatus::Skipped, e.to_string()),
+        };
+
+        VerificationResult {
+            action_id,
+            address,
+            provider,
+            status,
+            message,
+        }
+    }
+
+    /// A verifier with the config's `api_key` resolved, so it can come from
+    /// `!env` or `!secret`
+    fn verifier(&self, config: VerificationConfig) -> anyhow::Result<Verifier> {
+        let Some(api_key) = &config.api_key else {
+            return Ok(Verifier::new(config));
+        };
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+        let api_key = api_key
+            .resolve(alloy::dyn_abi::DynSolType::String, indexer)
+            .map_err(|e| anyhow::anyhow!("Failed to resolve verification api_key: {}", e))?;
+        let api_key = api_key
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Verification api_key must be a string"))?;
+        let api_key = Secret::new(api_key);
+        Ok(Verifier::new(config).with_api_key(api_key))
+    }
+
+    async fn verification_request(
+        &self,
+        data: &DeploymentData,
+        address: Address,
+    ) -> anyhow::Result<VerificationRequest> {
+        let indexer = self.indexer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Indexer not initialized"))?;
+
+        let key = data
+            .contract
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Deploy action has no contract reference"))?;
+        let contract = indexer.get_contract(key)?;
+        let initcode = build_initcode(data, indexer)?;
+        let chain_id = self.provider.get_chain_id().await?;
+
+        VerificationRequest::from_contract(
+            &contract,
+            address,
+            chain_id,
+            initcode.constructor_args(),
+            &self.data_dir,
+        )
+    }
+
     pub fn register_config(&mut self, config: Config) -> anyhow::Result<()> {
         // Create a new DataIndexer with the config's data references and variables
-        let mut data_indexer = DataIndexer::new(
-            config.data.clone(),
-            config.variables.clone(),
-            self.data_dir.clone(),
-        );
-        
-        // Also save variables to the base indexer for backward compatibility
-        for (k, v) in &config.variables {
-            data_indexer.save_variable(k, &v.ty, &v.value)?;
+        let data_indexer =
+            DataIndexer::for_config(&config, self.data_dir.clone(), self.secrets.clone())?;
+
+        // Catch misconfigured writes before anything is sent
+        for action in &config.actions {
+            match &action.action_data {
+                ActionData::Write(data) => validate_write(data, &data_indexer),
+                ActionData::Batch(data) => validate_batch(data, &data_indexer),
+                _ => Ok(()),
+            }
+            .map_err(|e| anyhow::anyhow!("Invalid action {}: {}", action.id, e))?;
         }
-        
+
         self.indexer = Some(data_indexer);
         self.config = config;
         Ok(())
     }
 }
+
+fn resolve_address<R: VariableResolver>(value: &VariableValue, resolver: &R) -> anyhow::Result<Address> {
+    value
+        .resolve(alloy::dyn_abi::DynSolType::Address, resolver)
+        .map_err(|e| anyhow::anyhow!("Failed to resolve address: {:?}", e))?
+        .as_address()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve address"))
+}
@@ -1,10 +1,10 @@
 use alloy::{
-    dyn_abi::{DynSolType, DynSolValue},
+    dyn_abi::{DynSolType, DynSolValue, Specifier},
     json_abi::Param,
 };
 use anyhow::bail;
-use std::{collections::HashMap, str::FromStr};
-use deployer_core::{DeployerError, Result, VariableResolver};
+use std::collections::HashMap;
+use deployer_core::{DeployerError, Result, Variable, VariableResolver};
 
 #[derive(Debug)]
 pub struct Indexer {
@@ -24,13 +24,19 @@ impl Indexer {
         let (_data, val) = self
             .variables
             .get(key)
-            .expect(&format!("No variable been indexed for {}", key));
+            .ok_or_else(|| anyhow::anyhow!("No variable been indexed for {}", key))?;
         Ok(val.clone())
     }
 
-    pub fn save_variable(&mut self, key: &str, ty: &str, value: &str) -> anyhow::Result<()> {
-        let dyn_type = DynSolType::from_str(ty)?;
-        let val = dyn_type.coerce_str(value)?;
+    pub fn save_variable(&mut self, key: &str, variable: &Variable) -> anyhow::Result<()> {
+        let val = variable.resolve(self)?;
+        self.save_variable_value(key, val)
+    }
+
+    pub fn save_variable_value(&mut self, key: &str, val: DynSolValue) -> anyhow::Result<()> {
+        let dyn_type = val
+            .as_type()
+            .ok_or_else(|| anyhow::anyhow!("Cannot determine type of variable {}", key))?;
         self.variables.insert(key.to_string(), (dyn_type, val));
         Ok(())
     }
@@ -45,7 +51,7 @@ impl Indexer {
             return Err(anyhow::anyhow!("Mismatched input and output lengths"));
         }
 
-        for (output_def, output) in output_definitions.into_iter().zip(outputs.into_iter()) {
+        for (output_def, output) in output_definitions.into_iter().zip(outputs) {
             self.index_output(id.clone(), output_def, output)?;
         }
 
@@ -53,10 +59,9 @@ impl Indexer {
     }
 
     pub fn get_output_value(&self, input_str: &str) -> anyhow::Result<DynSolValue> {
-        let (_data, val) = self.output_data.get(input_str).expect(&format!(
-            "No output data has been indexed for {}",
-            input_str
-        ));
+        let (_data, val) = self.output_data.get(input_str).ok_or_else(|| {
+            anyhow::anyhow!("No output data has been indexed for {}", input_str)
+        })?;
         Ok(val.clone())
     }
 
@@ -82,9 +87,12 @@ impl Indexer {
                 };
                 self.index_output(current_level.clone(), component_def, component_value)?;
             }
-        } else if let Some(t) = output.as_type() {
+        } else if let Some(t) = output.as_type().or_else(|| output_def.resolve().ok()) {
             match t {
                 DynSolType::Array(_) | DynSolType::FixedArray(_, _) => {
+                    // The whole array as well as each element, e.g. for `contains`
+                    self.output_data
+                        .insert(current_level.clone(), (t.clone(), output.clone()));
                     let elements = match t {
                         DynSolType::Array(_) => output.as_array().unwrap().to_vec(),
                         DynSolType::FixedArray(_, _) => output.as_fixed_array().unwrap().to_vec(),
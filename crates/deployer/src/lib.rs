@@ -3,4 +3,7 @@ pub mod data_indexer;
 pub mod execution;
 pub mod executor;
 pub mod indexer;
+pub mod safe;
+pub mod salt_miner;
 pub mod utils;
+pub mod verification;
@@ -0,0 +1,393 @@
+use crate::execution::{RevertDecoder, WriteCall};
+use alloy::{
+    contract::CallBuilder,
+    dyn_abi::{DynSolValue, JsonAbiExt},
+    json_abi::Function,
+    primitives::{address, keccak256, Address, Bytes, B256, U256},
+    providers::{network::TransactionBuilder, Provider},
+    rpc::types::{TransactionReceipt, TransactionRequest},
+    signers::{local::PrivateKeySigner, Signer},
+};
+use deployer_core::DeployerError;
+use serde::Serialize;
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Canonical `MultiSendCallOnly` v1.3.0 deployment
+pub const MULTI_SEND_CALL_ONLY: Address = address!("40A2aCCbd92BCA938b02010E17A5b8929b49130D");
+
+const DOMAIN_TYPE: &str = "EIP712Domain(uint256 chainId,address verifyingContract)";
+const SAFE_TX_TYPE: &str = "SafeTx(address to,uint256 value,bytes data,uint8 operation,uint256 safeTxGas,uint256 baseGas,uint256 gasPrice,address gasToken,address refundReceiver,uint256 nonce)";
+const EXEC_TRANSACTION: &str = "function execTransaction(address to, uint256 value, bytes data, uint8 operation, uint256 safeTxGas, uint256 baseGas, uint256 gasPrice, address gasToken, address refundReceiver, bytes signatures) payable returns (bool success)";
+const EXECUTION_FAILURE_EVENT: &str = "ExecutionFailure(bytes32,uint256)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Call = 0,
+    DelegateCall = 1,
+}
+
+/// A Safe transaction without refund parameters, which are always zero here
+#[derive(Debug, Clone)]
+pub struct SafeTransaction {
+    pub to: Address,
+    pub value: U256,
+    pub data: Bytes,
+    pub operation: Operation,
+    pub nonce: u64,
+}
+
+impl SafeTransaction {
+    /// A single call as is, or several batched through a `MultiSendCallOnly` delegate call
+    pub fn from_calls(calls: &[&WriteCall], multisend: Address, nonce: u64) -> Self {
+        match calls {
+            [call] => Self {
+                to: call.to,
+                value: call.value,
+                data: call.input.clone(),
+                operation: Operation::Call,
+                nonce,
+            },
+            _ => Self {
+                to: multisend,
+                value: U256::ZERO,
+                data: encode_multisend(calls),
+                operation: Operation::DelegateCall,
+                nonce,
+            },
+        }
+    }
+
+    /// EIP-712 `safeTxHash` that owners sign
+    pub fn hash(&self, safe: Address, chain_id: u64) -> B256 {
+        let domain_separator = keccak256(
+            DynSolValue::Tuple(vec![
+                DynSolValue::FixedBytes(keccak256(DOMAIN_TYPE), 32),
+                DynSolValue::Uint(U256::from(chain_id), 256),
+                DynSolValue::Address(safe),
+            ])
+            .abi_encode_params(),
+        );
+        let struct_hash = keccak256(
+            DynSolValue::Tuple(vec![
+                DynSolValue::FixedBytes(keccak256(SAFE_TX_TYPE), 32),
+                DynSolValue::Address(self.to),
+                DynSolValue::Uint(self.value, 256),
+                DynSolValue::FixedBytes(keccak256(&self.data), 32),
+                DynSolValue::Uint(U256::from(self.operation as u8), 8),
+                DynSolValue::Uint(U256::ZERO, 256),
+                DynSolValue::Uint(U256::ZERO, 256),
+                DynSolValue::Uint(U256::ZERO, 256),
+                DynSolValue::Address(Address::ZERO),
+                DynSolValue::Address(Address::ZERO),
+                DynSolValue::Uint(U256::from(self.nonce), 256),
+            ])
+            .abi_encode_params(),
+        );
+
+        keccak256([&[0x19, 0x01], domain_separator.as_slice(), struct_hash.as_slice()].concat())
+    }
+
+    /// `execTransaction` calldata carrying the owners' packed `signatures`
+    pub fn exec_transaction_input(&self, signatures: Bytes) -> anyhow::Result<Bytes> {
+        let function: Function = EXEC_TRANSACTION.parse()?;
+        let input = function.abi_encode_input(&[
+            DynSolValue::Address(self.to),
+            DynSolValue::Uint(self.value, 256),
+            DynSolValue::Bytes(self.data.to_vec()),
+            DynSolValue::Uint(U256::from(self.operation as u8), 8),
+            DynSolValue::Uint(U256::ZERO, 256),
+            DynSolValue::Uint(U256::ZERO, 256),
+            DynSolValue::Uint(U256::ZERO, 256),
+            DynSolValue::Address(Address::ZERO),
+            DynSolValue::Address(Address::ZERO),
+            DynSolValue::Bytes(signatures.to_vec()),
+        ])?;
+        Ok(Bytes::from(input))
+    }
+}
+
+/// Sign `safe_tx_hash` with each owner, packing the signatures in ascending
+/// owner order as `execTransaction` requires
+pub async fn sign_safe_transaction(
+    owners: &[PrivateKeySigner],
+    safe_tx_hash: B256,
+) -> anyhow::Result<Bytes> {
+    let mut owners: Vec<&PrivateKeySigner> = owners.iter().collect();
+    owners.sort_by_key(|owner| owner.address());
+    owners.dedup_by_key(|owner| owner.address());
+
+    let mut signatures = Vec::with_capacity(owners.len() * 65);
+    for owner in owners {
+        let signature = owner.sign_hash(&safe_tx_hash).await?;
+        signatures.extend_from_slice(&signature.as_bytes());
+    }
+    Ok(Bytes::from(signatures))
+}
+
+/// Sign `transaction` with `owners` and execute it from the provider's account.
+///
+/// Fails before sending if the owners don't meet the Safe's threshold, and
+/// afterwards if the transaction reverts or the Safe emits `ExecutionFailure`
+pub async fn execute_safe_transaction<P: Provider>(
+    provider: Arc<P>,
+    safe: Address,
+    transaction: &SafeTransaction,
+    safe_tx_hash: B256,
+    owners: &[PrivateKeySigner],
+) -> anyhow::Result<TransactionReceipt> {
+    // The same key listed twice only signs once
+    let mut owners: Vec<PrivateKeySigner> = owners.to_vec();
+    owners.sort_by_key(|owner| owner.address());
+    owners.dedup_by_key(|owner| owner.address());
+
+    for owner in &owners {
+        let is_owner = call_view(
+            provider.clone(),
+            safe,
+            "function isOwner(address owner) view returns (bool)",
+            &[DynSolValue::Address(owner.address())],
+        )
+        .await?;
+        if is_owner.first().and_then(DynSolValue::as_bool) != Some(true) {
+            anyhow::bail!("0x{:x} is not an owner of Safe 0x{:x}", owner.address(), safe);
+        }
+    }
+    let threshold = call_view(provider.clone(), safe, "function getThreshold() view returns (uint256)", &[])
+        .await?
+        .first()
+        .and_then(DynSolValue::as_uint)
+        .map(|(threshold, _)| threshold.to::<u64>())
+        .ok_or_else(|| anyhow::anyhow!("Safe 0x{:x} returned no threshold", safe))?;
+    if (owners.len() as u64) < threshold {
+        anyhow::bail!(
+            "Safe 0x{:x} needs {} signatures but only {} distinct owner keys are configured",
+            safe,
+            threshold,
+            owners.len()
+        );
+    }
+
+    let signatures = sign_safe_transaction(&owners, safe_tx_hash).await?;
+    let tx = TransactionRequest::default()
+        .with_to(safe)
+        .with_input(transaction.exec_transaction_input(signatures)?);
+
+    // Safe reverts with `GS0xx` reason strings, which decode without extra errors
+    if let Err(e) = provider.call(tx.clone()).await {
+        return Err(RevertDecoder::new(vec![]).transaction_failed("execTransaction", e));
+    }
+
+    let receipt = provider.send_transaction(tx).await?.get_receipt().await?;
+    if !receipt.status() {
+        return Err(DeployerError::TransactionFailed(format!(
+            "execTransaction: transaction 0x{:x} reverted",
+            receipt.transaction_hash
+        ))
+        .into());
+    }
+    if execution_failed(&receipt, safe) {
+        return Err(DeployerError::TransactionFailed(format!(
+            "Safe transaction 0x{:x} failed in transaction 0x{:x} (ExecutionFailure)",
+            safe_tx_hash, receipt.transaction_hash
+        ))
+        .into());
+    }
+    Ok(receipt)
+}
+
+/// Whether the Safe reported the inner call as failed
+pub fn execution_failed(receipt: &TransactionReceipt, safe: Address) -> bool {
+    let topic = keccak256(EXECUTION_FAILURE_EVENT);
+    receipt
+        .inner
+        .logs()
+        .iter()
+        .any(|log| log.address() == safe && log.topics().first() == Some(&topic))
+}
+
+/// `multiSend(bytes)` calldata packing each call as
+/// `operation (1) ++ to (20) ++ value (32) ++ data length (32) ++ data`
+pub fn encode_multisend(calls: &[&WriteCall]) -> Bytes {
+    let mut transactions = Vec::new();
+    for call in calls {
+        transactions.push(Operation::Call as u8);
+        transactions.extend_from_slice(call.to.as_slice());
+        transactions.extend_from_slice(&call.value.to_be_bytes::<32>());
+        transactions.extend_from_slice(&U256::from(call.input.len()).to_be_bytes::<32>());
+        transactions.extend_from_slice(&call.input);
+    }
+
+    let mut data = keccak256("multiSend(bytes)")[..4].to_vec();
+    data.extend(DynSolValue::Tuple(vec![DynSolValue::Bytes(transactions)]).abi_encode_params());
+    Bytes::from(data)
+}
+
+/// The Safe's current nonce
+pub async fn safe_nonce<P: Provider>(provider: Arc<P>, safe: Address) -> anyhow::Result<u64> {
+    call_view(provider, safe, "function nonce() view returns (uint256)", &[])
+        .await?
+        .first()
+        .and_then(DynSolValue::as_uint)
+        .map(|(nonce, _)| nonce.to::<u64>())
+        .ok_or_else(|| anyhow::anyhow!("Safe 0x{:x} returned no nonce", safe))
+}
+
+async fn call_view<P: Provider>(
+    provider: Arc<P>,
+    safe: Address,
+    signature: &str,
+    args: &[DynSolValue],
+) -> anyhow::Result<Vec<DynSolValue>> {
+    let function: Function = signature.parse()?;
+    CallBuilder::new_raw(provider, Bytes::from(function.abi_encode_input(args)?))
+        .to(safe)
+        .call_raw()
+        .with_decoder(&function)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to call {} on Safe 0x{:x}: {}", function.name, safe, e))
+}
+
+/// Batch file importable by the Safe Transaction Builder app
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxBuilderBatch {
+    version: String,
+    chain_id: String,
+    created_at: u128,
+    meta: TxBuilderMeta,
+    transactions: Vec<TxBuilderTransaction>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TxBuilderMeta {
+    name: String,
+    description: String,
+    tx_builder_version: String,
+    created_from_safe_address: String,
+    created_from_owner_address: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TxBuilderTransaction {
+    to: String,
+    value: String,
+    data: String,
+    contract_method: Option<serde_json::Value>,
+    contract_inputs_values: Option<serde_json::Value>,
+}
+
+impl TxBuilderBatch {
+    pub fn new(chain_id: u64, safe: Address, name: &str, calls: &[&WriteCall]) -> Self {
+        Self {
+            version: "1.0".to_string(),
+            chain_id: chain_id.to_string(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis(),
+            meta: TxBuilderMeta {
+                name: name.to_string(),
+                description: String::new(),
+                tx_builder_version: "1.16.5".to_string(),
+                created_from_safe_address: safe.to_checksum(None),
+                created_from_owner_address: String::new(),
+            },
+            transactions: calls
+                .iter()
+                .map(|call| TxBuilderTransaction {
+                    to: call.to.to_checksum(None),
+                    value: call.value.to_string(),
+                    data: call.input.to_string(),
+                    contract_method: None,
+                    contract_inputs_values: None,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Client for the Safe transaction service API, or anything compatible with it
+pub struct SafeTransactionService {
+    client: reqwest::Client,
+    url: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Proposal {
+    to: String,
+    value: String,
+    data: String,
+    operation: u8,
+    safe_tx_gas: String,
+    base_gas: String,
+    gas_price: String,
+    gas_token: String,
+    refund_receiver: String,
+    nonce: u64,
+    contract_transaction_hash: String,
+    sender: String,
+    signature: String,
+    origin: String,
+}
+
+impl SafeTransactionService {
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Propose `transaction` with the signature of one owner or delegate
+    pub async fn propose(
+        &self,
+        safe: Address,
+        transaction: &SafeTransaction,
+        safe_tx_hash: B256,
+        sender: Address,
+        signature: &[u8],
+        origin: &str,
+    ) -> anyhow::Result<()> {
+        let proposal = Proposal {
+            to: transaction.to.to_checksum(None),
+            value: transaction.value.to_string(),
+            data: transaction.data.to_string(),
+            operation: transaction.operation as u8,
+            safe_tx_gas: "0".to_string(),
+            base_gas: "0".to_string(),
+            gas_price: "0".to_string(),
+            gas_token: Address::ZERO.to_checksum(None),
+            refund_receiver: Address::ZERO.to_checksum(None),
+            nonce: transaction.nonce,
+            contract_transaction_hash: safe_tx_hash.to_string(),
+            sender: sender.to_checksum(None),
+            signature: format!("0x{}", hex::encode(signature)),
+            origin: origin.to_string(),
+        };
+
+        let url = format!(
+            "{}/api/v1/safes/{}/multisig-transactions/",
+            self.url,
+            safe.to_checksum(None)
+        );
+        let response = self.client.post(&url).json(&proposal).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Safe transaction service rejected the proposal ({}): {}", status, body);
+        }
+        Ok(())
+    }
+}
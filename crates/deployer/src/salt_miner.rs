@@ -0,0 +1,139 @@
+use alloy::primitives::{keccak256, Address, B256};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Address pattern a mined salt must produce; all given criteria must match
+#[derive(Debug, Clone, Default)]
+pub struct SaltPattern {
+    /// Nibbles the address must start with
+    prefix: Vec<u8>,
+    /// Nibbles the address must end with
+    suffix: Vec<u8>,
+    leading_zero_bytes: usize,
+}
+
+impl SaltPattern {
+    pub fn new(
+        prefix: Option<&str>,
+        suffix: Option<&str>,
+        leading_zero_bytes: usize,
+    ) -> anyhow::Result<Self> {
+        let nibbles = |pattern: Option<&str>| -> anyhow::Result<Vec<u8>> {
+            let pattern = pattern.unwrap_or_default().trim_start_matches("0x");
+            if pattern.len() > 40 {
+                anyhow::bail!("Address pattern {} is longer than an address", pattern);
+            }
+            pattern
+                .chars()
+                .map(|c| {
+                    c.to_digit(16)
+                        .map(|d| d as u8)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid address pattern {}", pattern))
+                })
+                .collect()
+        };
+
+        let pattern = Self {
+            prefix: nibbles(prefix)?,
+            suffix: nibbles(suffix)?,
+            leading_zero_bytes,
+        };
+        if pattern.prefix.is_empty() && pattern.suffix.is_empty() && leading_zero_bytes == 0 {
+            anyhow::bail!("Specify a prefix, suffix or number of leading zero bytes");
+        }
+        if leading_zero_bytes > 20 {
+            anyhow::bail!("An address has at most 20 leading zero bytes");
+        }
+        Ok(pattern)
+    }
+
+    pub fn matches(&self, address: &Address) -> bool {
+        if address.0[..self.leading_zero_bytes].iter().any(|b| *b != 0) {
+            return false;
+        }
+        let nibble = |i: usize| (address.0[i / 2] >> if i & 1 == 0 { 4 } else { 0 }) & 0x0f;
+        let suffix_start = 40 - self.suffix.len();
+        self.prefix.iter().enumerate().all(|(i, n)| nibble(i) == *n)
+            && self.suffix.iter().enumerate().all(|(i, n)| nibble(suffix_start + i) == *n)
+    }
+}
+
+/// A salt whose CREATE2 address matches the pattern
+#[derive(Debug, Clone)]
+pub struct MinedSalt {
+    pub salt: B256,
+    pub address: Address,
+    pub attempts: u64,
+}
+
+/// Brute-force salts for `factory` and `init_code_hash` across `threads`
+/// workers, giving up after `max_attempts` if set
+pub fn mine_salt(
+    factory: Address,
+    init_code_hash: B256,
+    pattern: &SaltPattern,
+    threads: usize,
+    max_attempts: Option<u64>,
+) -> Option<MinedSalt> {
+    let threads = threads.max(1);
+    let found = AtomicBool::new(false);
+    let attempts = AtomicU64::new(0);
+    let result = Mutex::new(None);
+
+    // Each worker searches its own random-looking 24-byte prefix, with the
+    // low 8 bytes as a counter
+    let seed = keccak256(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+            .to_be_bytes(),
+    );
+
+    std::thread::scope(|scope| {
+        for worker in 0..threads {
+            let (found, attempts, result) = (&found, &attempts, &result);
+            scope.spawn(move || {
+                let mut salt = keccak256([seed.as_slice(), &worker.to_be_bytes()].concat());
+                // Counting in batches keeps the shared counter off the hot path
+                const BATCH: u64 = 1024;
+
+                for counter in 0u64.. {
+                    if counter % BATCH == 0 {
+                        if found.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        let total = attempts.fetch_add(BATCH, Ordering::Relaxed);
+                        if max_attempts.is_some_and(|max| total >= max) {
+                            return;
+                        }
+                    }
+
+                    salt.0[24..].copy_from_slice(&counter.to_be_bytes());
+                    let address = factory.create2(salt, init_code_hash);
+                    if pattern.matches(&address) {
+                        if !found.swap(true, Ordering::Relaxed) {
+                            *result.lock().unwrap() = Some((salt, address));
+                        }
+                        return;
+                    }
+                }
+            });
+        }
+    });
+
+    let attempts = attempts.load(Ordering::Relaxed);
+    result
+        .into_inner()
+        .unwrap()
+        .map(|(salt, address)| MinedSalt {
+            salt,
+            address,
+            attempts,
+        })
+}
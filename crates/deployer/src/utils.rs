@@ -1,12 +1,12 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 
 use alloy::{
-    dyn_abi::{DynSolValue, JsonAbiExt},
-    json_abi::JsonAbi,
+    dyn_abi::JsonAbiExt,
+    json_abi::{Function, JsonAbi},
     primitives::Bytes,
 };
 
-use deployer_core::Action;
+use deployer_core::{Action, VariableResolver};
 
 pub fn generate_initcode(
     abi: JsonAbi,
@@ -31,13 +31,83 @@ pub fn generate_initcode(
     Ok(Bytes::from(initcode))
 }
 
-pub fn topological_sort(actions: Vec<Action>) -> anyhow::Result<Vec<Action>> {
-    // Map each action id to its corresponding Action object
-    let mut action_map: HashMap<String, Action> = HashMap::new();
-    for action in actions.clone() {
-        action_map.insert(action.id.clone(), action);
+/// Resolve the function an action calls, either from its `abi_item` or by
+/// looking up `function` in the ABI of the referenced `contract`
+pub fn resolve_function<R: VariableResolver>(
+    abi_item: Option<&str>,
+    contract: Option<&str>,
+    function: Option<&str>,
+    arg_count: usize,
+    resolver: &R,
+) -> anyhow::Result<Function> {
+    let function = match (abi_item, contract, function) {
+        (Some(abi_item), _, _) => abi_item.parse()?,
+        (None, Some(contract), Some(function)) => {
+            let abi = resolver
+                .get_contract_abi(contract)
+                .map_err(|e| anyhow::anyhow!("Failed to load ABI for {}: {}", contract, e))?;
+            find_function(&abi, function, arg_count)
+                .map_err(|e| anyhow::anyhow!("{} in contract {}", e, contract))?
+        }
+        _ => anyhow::bail!("Action must specify either abi_item or both contract and function"),
+    };
+
+    if function.inputs.len() != arg_count {
+        anyhow::bail!(
+            "Function {} expects {} args but {} were provided",
+            function.signature(),
+            function.inputs.len(),
+            arg_count
+        );
+    }
+
+    Ok(function)
+}
+
+/// Find a function by name or signature, using the argument count to pick
+/// between overloads when only a name is given
+pub fn find_function(abi: &JsonAbi, function: &str, arg_count: usize) -> anyhow::Result<Function> {
+    let function = function.trim();
+
+    if function.contains('(') {
+        let signature: String = function.chars().filter(|c| !c.is_whitespace()).collect();
+        return abi
+            .functions()
+            .find(|f| f.signature() == signature)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Function {} not found", signature));
+    }
+
+    let overloads = abi
+        .function(function)
+        .ok_or_else(|| anyhow::anyhow!("Function {} not found", function))?;
+
+    match overloads.as_slice() {
+        [single] => Ok(single.clone()),
+        _ => {
+            let mut candidates = overloads.iter().filter(|f| f.inputs.len() == arg_count);
+            match (candidates.next(), candidates.next()) {
+                (Some(f), None) => Ok(f.clone()),
+                _ => {
+                    let signatures: Vec<String> = overloads.iter().map(|f| f.signature()).collect();
+                    anyhow::bail!(
+                        "Function {} is overloaded, specify one of: {}",
+                        function,
+                        signatures.join(", ")
+                    )
+                }
+            }
+        }
     }
+}
 
+pub fn topological_sort(actions: Vec<Action>) -> anyhow::Result<Vec<Action>> {
+    Ok(topological_levels(actions)?.into_iter().flatten().collect())
+}
+
+/// Group actions into levels whose dependencies are all in earlier levels.
+/// Actions within a level are independent and keep their config order
+pub fn topological_levels(actions: Vec<Action>) -> anyhow::Result<Vec<Vec<Action>>> {
     // Initialize in-degree map and adjacency list
     let mut in_degree: HashMap<String, usize> = HashMap::new();
     let mut adjacency_list: HashMap<String, HashSet<String>> = HashMap::new();
@@ -56,41 +126,34 @@ pub fn topological_sort(actions: Vec<Action>) -> anyhow::Result<Vec<Action>> {
         }
     }
 
-    // Initialize the queue with actions having no dependencies
-    let mut queue: VecDeque<String> = VecDeque::new();
-    for (id, &degree) in &in_degree {
-        if degree == 0 {
-            queue.push_back(id.clone());
+    let total = actions.len();
+    let mut remaining = actions;
+    let mut levels: Vec<Vec<Action>> = Vec::new();
+    let mut placed = 0;
+
+    while placed < total {
+        // Actions with no unmet dependencies form the next level
+        let (level, rest): (Vec<Action>, Vec<Action>) = remaining
+            .into_iter()
+            .partition(|action| in_degree.get(&action.id) == Some(&0));
+        if level.is_empty() {
+            anyhow::bail!("There is a cycle in the dependencies");
         }
-    }
 
-    // Perform the topological sort
-    let mut ordered_action_ids: Vec<String> = Vec::new();
-    while let Some(action_id) = queue.pop_front() {
-        ordered_action_ids.push(action_id.clone());
-
-        if let Some(neighbors) = adjacency_list.get(&action_id) {
-            for neighbor in neighbors {
-                if let Some(degree) = in_degree.get_mut(neighbor) {
-                    *degree -= 1;
-                    if *degree == 0 {
-                        queue.push_back(neighbor.clone());
+        for action in &level {
+            if let Some(neighbors) = adjacency_list.get(&action.id) {
+                for neighbor in neighbors {
+                    if let Some(degree) = in_degree.get_mut(neighbor) {
+                        *degree -= 1;
                     }
                 }
             }
         }
-    }
 
-    // Check for cycles
-    if ordered_action_ids.len() != actions.len() {
-        anyhow::bail!("There is a cycle in the dependencies");
+        placed += level.len();
+        levels.push(level);
+        remaining = rest;
     }
 
-    // Map the ordered ids back to actions
-    let ordered_actions = ordered_action_ids
-        .into_iter()
-        .map(|id| action_map.remove(&id).unwrap())
-        .collect();
-
-    Ok(ordered_actions)
+    Ok(levels)
 }
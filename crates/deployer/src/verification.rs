@@ -0,0 +1,291 @@
+use alloy::primitives::{Address, Bytes};
+use deployer_core::{ContractData, Secret, VerificationConfig, VerificationProvider, VerificationStatus};
+use serde_json::{json, Value};
+use std::{path::Path, time::Duration};
+
+/// Everything a block explorer needs to verify one deployed contract
+#[derive(Debug, Clone)]
+pub struct VerificationRequest {
+    pub address: Address,
+    pub chain_id: u64,
+    /// Fully qualified name, e.g. `src/LightAccountFactory.sol:LightAccountFactory`
+    pub contract_name: String,
+    /// Full compiler version, e.g. `0.8.23+commit.f704f362`
+    pub compiler_version: String,
+    pub standard_json_input: Value,
+    pub constructor_args: Bytes,
+}
+
+impl VerificationRequest {
+    /// Build a request from the contract's artifact, reading sources relative to
+    /// `source_root` when the artifact's metadata doesn't embed them
+    pub fn from_contract(
+        contract: &ContractData,
+        address: Address,
+        chain_id: u64,
+        constructor_args: Bytes,
+        source_root: &Path,
+    ) -> anyhow::Result<Self> {
+        let source = contract
+            .source
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Artifact for {} has no source path", contract.name))?;
+        let compiler_version = contract
+            .compiler_version
+            .as_deref()
+            .map(normalize_compiler_version)
+            .ok_or_else(|| anyhow::anyhow!("Artifact for {} has no compiler version", contract.name))?;
+        let standard_json_input = match &contract.standard_json_input {
+            Some(input) => input.clone(),
+            None => standard_json_input_from_metadata(contract, source_root)?,
+        };
+
+        Ok(Self {
+            address,
+            chain_id,
+            contract_name: format!("{}:{}", source, contract.name),
+            compiler_version,
+            standard_json_input,
+            constructor_args,
+        })
+    }
+}
+
+/// Reconstruct the standard-JSON input from the metadata solc embeds in artifacts
+fn standard_json_input_from_metadata(
+    contract: &ContractData,
+    source_root: &Path,
+) -> anyhow::Result<Value> {
+    let sources = contract
+        .metadata
+        .get("sources")
+        .and_then(Value::as_object)
+        .ok_or_else(|| anyhow::anyhow!("Artifact for {} has no source metadata", contract.name))?;
+
+    let mut input_sources = serde_json::Map::new();
+    for (path, source) in sources {
+        let content = match source.get("content").and_then(Value::as_str) {
+            Some(content) => content.to_string(),
+            None => std::fs::read_to_string(source_root.join(path))
+                .map_err(|e| anyhow::anyhow!("Failed to read source {}: {}", path, e))?,
+        };
+        input_sources.insert(path.clone(), json!({ "content": content }));
+    }
+
+    let mut settings = contract.metadata.get("settings").cloned().unwrap_or_else(|| json!({}));
+    if let Some(settings) = settings.as_object_mut() {
+        // Metadata-only keys that standard-JSON input doesn't accept
+        settings.remove("compilationTarget");
+        settings.insert(
+            "outputSelection".to_string(),
+            json!({ "*": { "*": ["abi", "evm.bytecode", "evm.deployedBytecode", "metadata"] } }),
+        );
+    }
+
+    Ok(json!({
+        "language": contract.metadata.get("language").cloned().unwrap_or_else(|| json!("Solidity")),
+        "sources": input_sources,
+        "settings": settings,
+    }))
+}
+
+/// Strip the `v` prefix and platform suffix from a solc version string,
+/// e.g. `0.8.23+commit.f704f362.Linux.g++` becomes `0.8.23+commit.f704f362`
+pub fn normalize_compiler_version(version: &str) -> String {
+    let version = version.trim().trim_start_matches('v');
+    match version.split_once("+commit.") {
+        Some((release, rest)) => {
+            let commit: String = rest.chars().take_while(char::is_ascii_hexdigit).collect();
+            format!("{}+commit.{}", release, commit)
+        }
+        None => version.to_string(),
+    }
+}
+
+/// Submits contracts to an Etherscan- or Sourcify-compatible API and polls
+/// until verification completes
+pub struct Verifier {
+    config: VerificationConfig,
+    api_key: Option<Secret>,
+    client: reqwest::Client,
+}
+
+impl Verifier {
+    pub fn new(config: VerificationConfig) -> Self {
+        Self {
+            config,
+            api_key: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Use the resolved value of the config's `api_key`
+    pub fn with_api_key(mut self, api_key: Secret) -> Self {
+        self.api_key = Some(api_key);
+        self
+    }
+
+    pub async fn verify(
+        &self,
+        request: &VerificationRequest,
+    ) -> anyhow::Result<(VerificationStatus, String)> {
+        match self.config.provider {
+            VerificationProvider::Etherscan => self.verify_etherscan(request).await,
+            VerificationProvider::Sourcify => self.verify_sourcify(request).await,
+        }
+    }
+
+    async fn verify_etherscan(
+        &self,
+        request: &VerificationRequest,
+    ) -> anyhow::Result<(VerificationStatus, String)> {
+        let api_key = self.api_key.as_ref().map(Secret::expose).unwrap_or_default();
+        let chain_id = request.chain_id.to_string();
+        let source_code = serde_json::to_string(&request.standard_json_input)?;
+        let compiler_version = format!("v{}", request.compiler_version);
+        let address = request.address.to_checksum(None);
+        let constructor_args = hex::encode(&request.constructor_args);
+
+        let response: Value = self
+            .client
+            .post(&self.config.url)
+            .query(&[("chainid", chain_id.as_str())])
+            .form(&[
+                ("apikey", api_key),
+                ("module", "contract"),
+                ("action", "verifysourcecode"),
+                ("contractaddress", address.as_str()),
+                ("sourceCode", source_code.as_str()),
+                ("codeformat", "solidity-standard-json-input"),
+                ("contractname", request.contract_name.as_str()),
+                ("compilerversion", compiler_version.as_str()),
+                // Etherscan's own spelling
+                ("constructorArguements", constructor_args.as_str()),
+            ])
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        let result = response.get("result").and_then(Value::as_str).unwrap_or_default();
+        if response.get("status").and_then(Value::as_str) != Some("1") {
+            if result.to_lowercase().contains("already verified") {
+                return Ok((VerificationStatus::AlreadyVerified, result.to_string()));
+            }
+            return Ok((VerificationStatus::Failed, result.to_string()));
+        }
+
+        let guid = result.to_string();
+        for _ in 0..self.config.max_attempts {
+            tokio::time::sleep(Duration::from_secs(self.config.poll_interval_secs)).await;
+
+            let status: Value = self
+                .client
+                .get(&self.config.url)
+                .query(&[
+                    ("chainid", chain_id.as_str()),
+                    ("apikey", api_key),
+                    ("module", "contract"),
+                    ("action", "checkverifystatus"),
+                    ("guid", guid.as_str()),
+                ])
+                .send()
+                .await?
+                .json()
+                .await?;
+
+            let result = status.get("result").and_then(Value::as_str).unwrap_or_default();
+            let lower = result.to_lowercase();
+            if lower.contains("pending") || lower.contains("in queue") {
+                continue;
+            }
+            if lower.contains("already verified") {
+                return Ok((VerificationStatus::AlreadyVerified, result.to_string()));
+            }
+            if status.get("status").and_then(Value::as_str) == Some("1") {
+                return Ok((VerificationStatus::Verified, result.to_string()));
+            }
+            return Ok((VerificationStatus::Failed, result.to_string()));
+        }
+
+        Ok((VerificationStatus::Pending, format!("Verification {} still pending", guid)))
+    }
+
+    async fn verify_sourcify(
+        &self,
+        request: &VerificationRequest,
+    ) -> anyhow::Result<(VerificationStatus, String)> {
+        let base_url = self.config.url.trim_end_matches('/');
+        let submit_url = format!(
+            "{}/v2/verify/{}/{}",
+            base_url,
+            request.chain_id,
+            request.address.to_checksum(None)
+        );
+
+        let response = self
+            .client
+            .post(&submit_url)
+            .json(&json!({
+                "stdJsonInput": request.standard_json_input,
+                "compilerVersion": request.compiler_version,
+                "contractIdentifier": request.contract_name,
+            }))
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::CONFLICT {
+            return Ok((VerificationStatus::AlreadyVerified, "Contract already verified".to_string()));
+        }
+        let submitted: Value = response.json().await?;
+        let verification_id = match submitted.get("verificationId").and_then(Value::as_str) {
+            Some(id) => id.to_string(),
+            None => return Ok((VerificationStatus::Failed, sourcify_error(&submitted))),
+        };
+
+        let status_url = format!("{}/v2/verify/{}", base_url, verification_id);
+        for _ in 0..self.config.max_attempts {
+            tokio::time::sleep(Duration::from_secs(self.config.poll_interval_secs)).await;
+
+            let job: Value = self.client.get(&status_url).send().await?.json().await?;
+            if job.get("isJobCompleted").and_then(Value::as_bool) != Some(true) {
+                continue;
+            }
+
+            if job.get("error").is_some_and(|e| !e.is_null()) {
+                let code = job
+                    .get("error")
+                    .and_then(|e| e.get("customCode"))
+                    .and_then(Value::as_str);
+                let status = if code == Some("already_verified") {
+                    VerificationStatus::AlreadyVerified
+                } else {
+                    VerificationStatus::Failed
+                };
+                return Ok((status, sourcify_error(&job)));
+            }
+
+            let matched = job
+                .get("contract")
+                .and_then(|c| c.get("match"))
+                .and_then(Value::as_str)
+                .unwrap_or("no match");
+            return Ok((VerificationStatus::Verified, matched.to_string()));
+        }
+
+        Ok((
+            VerificationStatus::Pending,
+            format!("Verification {} still pending", verification_id),
+        ))
+    }
+}
+
+fn sourcify_error(response: &Value) -> String {
+    response
+        .get("error")
+        .and_then(|e| e.get("message"))
+        .or_else(|| response.get("message"))
+        .and_then(Value::as_str)
+        .unwrap_or("Unknown Sourcify error")
+        .to_string()
+}
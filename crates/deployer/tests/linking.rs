@@ -0,0 +1,123 @@
+use alloy::{
+    dyn_abi::DynSolValue,
+    primitives::{address, Address},
+};
+use deployer::execution::build_initcode;
+use deployer_core::{
+    library_placeholder, ContractData, DeployerError, DeploymentData, Result, VariableResolver,
+    VariableValue,
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+const LIBRARY: Address = address!("1111111111111111111111111111111111111111");
+
+/// Serves one contract, `lib_user`, whose bytecode calls `src/Lib.sol:Lib`
+struct Artifacts {
+    contract: ContractData,
+}
+
+impl Artifacts {
+    fn new() -> Self {
+        let bytecode = format!("0x6080{}00", library_placeholder("src/Lib.sol:Lib"));
+        let contract = serde_json::from_value(json!({
+            "name": "LibUser",
+            "bytecode": bytecode,
+            "abi": [],
+            "constructor": null,
+            "link_references": {
+                "src/Lib.sol": { "Lib": [{ "start": 2, "length": 20 }] }
+            },
+        }))
+        .unwrap();
+        Self { contract }
+    }
+}
+
+impl VariableResolver for Artifacts {
+    fn get_variable(&self, key: &str) -> Result<DynSolValue> {
+        Err(DeployerError::VariableNotFound(key.to_string()))
+    }
+
+    fn get_output(&self, id: &str) -> Result<DynSolValue> {
+        Err(DeployerError::OutputNotFound(id.to_string()))
+    }
+
+    fn get_data(&self, path: &str) -> Result<DynSolValue> {
+        match path {
+            "lib_user.bytecode" => Ok(DynSolValue::String(self.contract.bytecode.clone())),
+            _ => Err(DeployerError::Config(format!("Data reference not found: {}", path))),
+        }
+    }
+
+    fn get_contract(&self, key: &str) -> Result<ContractData> {
+        match key {
+            "lib_user" => Ok(self.contract.clone()),
+            _ => Err(DeployerError::Config(format!("Data reference not found: {}", key))),
+        }
+    }
+}
+
+fn deployment(bytecode: VariableValue, contract: Option<&str>, library: &str) -> DeploymentData {
+    DeploymentData {
+        address: None,
+        constructor_args: vec![],
+        salt: VariableValue::Value(format!("0x{}", "00".repeat(32))),
+        constructor_abi_item: None,
+        bytecode: Some(bytecode),
+        contract: contract.map(str::to_string),
+        errors: vec![],
+        libraries: HashMap::from([(
+            library.to_string(),
+            VariableValue::Value(LIBRARY.to_string()),
+        )]),
+    }
+}
+
+fn linked_code() -> Vec<u8> {
+    let mut code = vec![0x60, 0x80];
+    code.extend_from_slice(LIBRARY.as_slice());
+    code.push(0x00);
+    code
+}
+
+#[test]
+fn links_data_bytecode_by_short_name() {
+    let data = deployment(VariableValue::Data("lib_user.bytecode".to_string()), None, "Lib");
+    let initcode = build_initcode(&data, &Artifacts::new()).unwrap();
+
+    assert_eq!(initcode.code.to_vec(), linked_code());
+}
+
+#[test]
+fn links_data_bytecode_by_qualified_name() {
+    let data = deployment(
+        VariableValue::Data("lib_user.bytecode".to_string()),
+        None,
+        "src/Lib.sol:Lib",
+    );
+    let initcode = build_initcode(&data, &Artifacts::new()).unwrap();
+
+    assert_eq!(initcode.code.to_vec(), linked_code());
+}
+
+#[test]
+fn links_explicit_bytecode_by_short_name_with_contract() {
+    let artifacts = Artifacts::new();
+    let data = deployment(
+        VariableValue::Value(artifacts.contract.bytecode.clone()),
+        Some("lib_user"),
+        "Lib",
+    );
+    let initcode = build_initcode(&data, &artifacts).unwrap();
+
+    assert_eq!(initcode.code.to_vec(), linked_code());
+}
+
+#[test]
+fn rejects_bytecode_with_an_unknown_library() {
+    let data = deployment(VariableValue::Data("lib_user.bytecode".to_string()), None, "Other");
+    let error = build_initcode(&data, &Artifacts::new()).unwrap_err();
+
+    assert!(error.to_string().contains("src/Lib.sol:Lib"), "{}", error);
+}
@@ -0,0 +1,212 @@
+use alloy::{
+    primitives::{address, b256, hex, Address, Bytes, Signature, U256},
+    signers::{local::PrivateKeySigner, Signer},
+    sol,
+    sol_types::{Eip712Domain, SolStruct},
+};
+use axum::{extract::Path, http::StatusCode, routing::post, Json, Router};
+use deployer::{
+    execution::WriteCall,
+    safe::{encode_multisend, Operation, SafeTransaction, SafeTransactionService},
+};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+
+const SAFE: Address = address!("5aFE3855358E112B5647B952709E6165e1c1eEEe");
+const MULTISEND: Address = address!("40A2aCCbd92BCA938b02010E17A5b8929b49130D");
+
+sol! {
+    struct SafeTx {
+        address to;
+        uint256 value;
+        bytes data;
+        uint8 operation;
+        uint256 safeTxGas;
+        uint256 baseGas;
+        uint256 gasPrice;
+        address gasToken;
+        address refundReceiver;
+        uint256 nonce;
+    }
+}
+
+fn call(to: Address, value: u64, input: &str) -> WriteCall {
+    WriteCall {
+        function: "function setX(uint256)".parse().unwrap(),
+        to,
+        value: U256::from(value),
+        input: input.parse().unwrap(),
+    }
+}
+
+fn transaction() -> SafeTransaction {
+    SafeTransaction {
+        to: address!("1111111111111111111111111111111111111111"),
+        value: U256::from(1_000_000_000_000_000_000u128),
+        data: Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]),
+        operation: Operation::Call,
+        nonce: 7,
+    }
+}
+
+#[test]
+fn safe_tx_hash_matches_eip712_signing_hash() {
+    let transaction = transaction();
+    let expected = SafeTx {
+        to: transaction.to,
+        value: transaction.value,
+        data: transaction.data.clone(),
+        operation: 0,
+        safeTxGas: U256::ZERO,
+        baseGas: U256::ZERO,
+        gasPrice: U256::ZERO,
+        gasToken: Address::ZERO,
+        refundReceiver: Address::ZERO,
+        nonce: U256::from(7),
+    }
+    .eip712_signing_hash(&Eip712Domain {
+        chain_id: Some(U256::from(11155111)),
+        verifying_contract: Some(SAFE),
+        ..Default::default()
+    });
+
+    // Pinned so a change to either encoding shows up here
+    assert_eq!(
+        expected,
+        b256!("719c6ed17571a6a54732301ff7a49aa218cd13464b6ed2d18ddbf4592481425a")
+    );
+    assert_eq!(transaction.hash(SAFE, 11155111), expected);
+}
+
+#[test]
+fn safe_tx_hash_depends_on_chain_and_safe() {
+    let transaction = transaction();
+    let hash = transaction.hash(SAFE, 1);
+
+    assert_ne!(hash, transaction.hash(SAFE, 11155111));
+    assert_ne!(hash, transaction.hash(MULTISEND, 1));
+}
+
+#[test]
+fn multisend_packs_calls_back_to_back() {
+    let first = call(address!("1111111111111111111111111111111111111111"), 0, "0xaabbccdd");
+    let second = call(address!("2222222222222222222222222222222222222222"), 5, "0x");
+
+    let packed = concat!(
+        // operation, to, value, data length, data
+        "00",
+        "1111111111111111111111111111111111111111",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "0000000000000000000000000000000000000000000000000000000000000004",
+        "aabbccdd",
+        "00",
+        "2222222222222222222222222222222222222222",
+        "0000000000000000000000000000000000000000000000000000000000000005",
+        "0000000000000000000000000000000000000000000000000000000000000000",
+    );
+    let expected = hex::decode(
+        [
+            // multiSend(bytes)
+            "8d80ff0a",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            // 2 * (1 + 20 + 32 + 32) + 4 bytes
+            "00000000000000000000000000000000000000000000000000000000000000ae",
+            packed,
+            // padding to a 32-byte boundary
+            &"00".repeat(18),
+        ]
+        .concat(),
+    )
+    .unwrap();
+
+    assert_eq!(encode_multisend(&[&first, &second]).to_vec(), expected);
+}
+
+#[test]
+fn batches_several_calls_through_multisend() {
+    let first = call(address!("1111111111111111111111111111111111111111"), 0, "0xaabbccdd");
+    let second = call(address!("2222222222222222222222222222222222222222"), 5, "0x");
+
+    let single = SafeTransaction::from_calls(&[&first], MULTISEND, 3);
+    assert_eq!(single.to, first.to);
+    assert_eq!(single.operation, Operation::Call);
+    assert_eq!(single.data, first.input);
+
+    let batch = SafeTransaction::from_calls(&[&first, &second], MULTISEND, 3);
+    assert_eq!(batch.to, MULTISEND);
+    assert_eq!(batch.value, U256::ZERO);
+    assert_eq!(batch.operation, Operation::DelegateCall);
+    assert_eq!(batch.data, encode_multisend(&[&first, &second]));
+    assert_eq!(batch.nonce, 3);
+}
+
+async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+#[tokio::test]
+async fn proposes_a_signed_transaction_to_the_service() {
+    let received: Arc<Mutex<Vec<(String, Value)>>> = Arc::default();
+    let recorder = received.clone();
+    let app = Router::new().route(
+        "/api/v1/safes/:safe/multisig-transactions/",
+        post(move |Path(safe): Path<String>, Json(body): Json<Value>| async move {
+            recorder.lock().unwrap().push((safe, body));
+            StatusCode::CREATED
+        }),
+    );
+    let url = serve(app).await;
+
+    let signer = PrivateKeySigner::from_slice(&[0x11; 32]).unwrap();
+    let transaction = transaction();
+    let safe_tx_hash = transaction.hash(SAFE, 11155111);
+    let signature = signer.sign_hash(&safe_tx_hash).await.unwrap().as_bytes();
+
+    SafeTransactionService::new(&format!("{}/", url))
+        .propose(SAFE, &transaction, safe_tx_hash, signer.address(), &signature, "pipeline")
+        .await
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+    let (safe, body) = &received[0];
+    assert_eq!(safe, &SAFE.to_checksum(None));
+    assert_eq!(body["to"], transaction.to.to_checksum(None));
+    assert_eq!(body["value"], "1000000000000000000");
+    assert_eq!(body["data"], "0xdeadbeef");
+    assert_eq!(body["operation"], 0);
+    assert_eq!(body["safeTxGas"], "0");
+    assert_eq!(body["gasToken"], Address::ZERO.to_checksum(None));
+    assert_eq!(body["nonce"], 7);
+    assert_eq!(body["contractTransactionHash"], safe_tx_hash.to_string());
+    assert_eq!(body["sender"], signer.address().to_checksum(None));
+    assert_eq!(body["origin"], "pipeline");
+
+    let posted = hex::decode(body["signature"].as_str().unwrap()).unwrap();
+    let recovered = Signature::from_raw(&posted)
+        .unwrap()
+        .recover_address_from_prehash(&safe_tx_hash)
+        .unwrap();
+    assert_eq!(recovered, signer.address());
+}
+
+#[tokio::test]
+async fn reports_a_rejected_proposal() {
+    let app = Router::new().route(
+        "/api/v1/safes/:safe/multisig-transactions/",
+        post(|| async { (StatusCode::UNPROCESSABLE_ENTITY, "nonce already executed") }),
+    );
+    let url = serve(app).await;
+
+    let transaction = transaction();
+    let error = SafeTransactionService::new(&url)
+        .propose(SAFE, &transaction, transaction.hash(SAFE, 1), SAFE, &[0; 65], "pipeline")
+        .await
+        .unwrap_err();
+
+    assert!(error.to_string().contains("422"), "{}", error);
+    assert!(error.to_string().contains("nonce already executed"), "{}", error);
+}
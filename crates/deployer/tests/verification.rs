@@ -0,0 +1,214 @@
+use alloy::primitives::{address, bytes, Address};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Form, Json, Router,
+};
+use deployer::verification::{VerificationRequest, Verifier};
+use deployer_core::{
+    Secret, VariableValue, VerificationConfig, VerificationProvider, VerificationStatus,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+const CONTRACT: Address = address!("4f84a207A80c39E9e8BaE717c1F25bA7AD1fB08F");
+
+/// Query or path parameters of a request, and its body
+type Request = (HashMap<String, String>, Value);
+
+/// Requests the mock explorer received, in order
+#[derive(Clone, Default)]
+struct Received(Arc<Mutex<Vec<Request>>>);
+
+impl Received {
+    fn record(&self, query: HashMap<String, String>, body: Value) -> usize {
+        let mut requests = self.0.lock().unwrap();
+        requests.push((query, body));
+        requests.len()
+    }
+
+    fn take(&self) -> Vec<Request> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+fn config(url: String, provider: VerificationProvider) -> VerificationConfig {
+    VerificationConfig {
+        enabled: true,
+        url,
+        provider,
+        api_key: Some(VariableValue::Secret("etherscan_api_key".to_string())),
+        poll_interval_secs: 0,
+        max_attempts: 3,
+    }
+}
+
+/// A verifier as the executor builds it, with `api_key` already resolved
+fn verifier(url: String, provider: VerificationProvider) -> Verifier {
+    Verifier::new(config(url, provider)).with_api_key(Secret::new("test-key"))
+}
+
+fn request() -> VerificationRequest {
+    VerificationRequest {
+        address: CONTRACT,
+        chain_id: 11155111,
+        contract_name: "src/Factory.sol:Factory".to_string(),
+        compiler_version: "0.8.23+commit.f704f362".to_string(),
+        standard_json_input: json!({ "language": "Solidity", "sources": {}, "settings": {} }),
+        constructor_args: bytes!("00000000000000000000000000000000000000000000000000000000000000aa"),
+    }
+}
+
+#[tokio::test]
+async fn etherscan_submits_then_polls_until_verified() {
+    let received = Received::default();
+    let app = Router::new()
+        .route(
+            "/api",
+            post(
+                |State(received): State<Received>,
+                 Query(query): Query<HashMap<String, String>>,
+                 Form(form): Form<HashMap<String, String>>| async move {
+                    received.record(query, json!(form));
+                    Json(json!({ "status": "1", "message": "OK", "result": "guid-1" }))
+                },
+            )
+            .get(
+                |State(received): State<Received>,
+                 Query(query): Query<HashMap<String, String>>| async move {
+                    let checks = received.record(query, Value::Null) - 1;
+                    if checks < 2 {
+                        Json(json!({ "status": "0", "result": "Pending in queue" }))
+                    } else {
+                        Json(json!({ "status": "1", "result": "Pass - Verified" }))
+                    }
+                },
+            ),
+        )
+        .with_state(received.clone());
+    let url = serve(app).await;
+
+    let verifier = verifier(format!("{}/api", url), VerificationProvider::Etherscan);
+    let (status, message) = verifier.verify(&request()).await.unwrap();
+
+    assert_eq!(status, VerificationStatus::Verified);
+    assert_eq!(message, "Pass - Verified");
+
+    let requests = received.take();
+    assert_eq!(requests.len(), 3);
+
+    let (query, form) = &requests[0];
+    assert_eq!(query["chainid"], "11155111");
+    assert_eq!(form["action"], "verifysourcecode");
+    assert_eq!(form["apikey"], "test-key");
+    assert_eq!(form["contractaddress"], CONTRACT.to_checksum(None));
+    assert_eq!(form["contractname"], "src/Factory.sol:Factory");
+    assert_eq!(form["compilerversion"], "v0.8.23+commit.f704f362");
+    assert_eq!(form["codeformat"], "solidity-standard-json-input");
+    assert_eq!(
+        form["constructorArguements"],
+        "00000000000000000000000000000000000000000000000000000000000000aa"
+    );
+
+    for (query, _) in &requests[1..] {
+        assert_eq!(query["action"], "checkverifystatus");
+        assert_eq!(query["guid"], "guid-1");
+    }
+}
+
+#[tokio::test]
+async fn etherscan_reports_already_verified_on_submit() {
+    let app = Router::new().route(
+        "/api",
+        post(|| async {
+            Json(json!({ "status": "0", "result": "Contract source code already verified" }))
+        }),
+    );
+    let url = serve(app).await;
+
+    let verifier = verifier(format!("{}/api", url), VerificationProvider::Etherscan);
+    let (status, _) = verifier.verify(&request()).await.unwrap();
+
+    assert_eq!(status, VerificationStatus::AlreadyVerified);
+}
+
+#[tokio::test]
+async fn sourcify_submits_then_polls_the_job() {
+    let received = Received::default();
+    let app = Router::new()
+        .route(
+            "/v2/verify/:chain_id/:address",
+            post(
+                |State(received): State<Received>,
+                 Path((chain_id, address)): Path<(String, String)>,
+                 Json(body): Json<Value>| async move {
+                    let path = HashMap::from([
+                        ("chain_id".to_string(), chain_id),
+                        ("address".to_string(), address),
+                    ]);
+                    received.record(path, body);
+                    (StatusCode::ACCEPTED, Json(json!({ "verificationId": "job-1" })))
+                },
+            ),
+        )
+        .route(
+            "/v2/verify/:verification_id",
+            get(
+                |State(received): State<Received>, Path(verification_id): Path<String>| async move {
+                    let path = HashMap::from([("verification_id".to_string(), verification_id)]);
+                    let checks = received.record(path, Value::Null) - 1;
+                    Json(json!({
+                        "isJobCompleted": checks >= 2,
+                        "contract": { "match": "exact_match" },
+                    }))
+                },
+            ),
+        )
+        .with_state(received.clone());
+    let url = serve(app).await;
+
+    let verifier = verifier(format!("{}/", url), VerificationProvider::Sourcify);
+    let (status, message) = verifier.verify(&request()).await.unwrap();
+
+    assert_eq!(status, VerificationStatus::Verified);
+    assert_eq!(message, "exact_match");
+
+    let requests = received.take();
+    assert_eq!(requests.len(), 3);
+
+    let (path, body) = &requests[0];
+    assert_eq!(path["chain_id"], "11155111");
+    assert_eq!(path["address"], CONTRACT.to_checksum(None));
+    assert_eq!(body["compilerVersion"], "0.8.23+commit.f704f362");
+    assert_eq!(body["contractIdentifier"], "src/Factory.sol:Factory");
+    assert_eq!(body["stdJsonInput"]["language"], "Solidity");
+
+    for (path, _) in &requests[1..] {
+        assert_eq!(path["verification_id"], "job-1");
+    }
+}
+
+#[tokio::test]
+async fn sourcify_reports_conflict_as_already_verified() {
+    let app = Router::new().route(
+        "/v2/verify/:chain_id/:address",
+        post(|| async { (StatusCode::CONFLICT, Json(json!({ "customCode": "already_verified" }))) }),
+    );
+    let url = serve(app).await;
+
+    let verifier = verifier(url, VerificationProvider::Sourcify);
+    let (status, _) = verifier.verify(&request()).await.unwrap();
+
+    assert_eq!(status, VerificationStatus::AlreadyVerified);
+}